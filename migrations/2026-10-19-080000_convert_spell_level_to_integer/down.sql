@@ -0,0 +1,18 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE spells
+DROP CONSTRAINT spells_level_range;
+
+ALTER TABLE spells
+ALTER COLUMN level TYPE VARCHAR USING (
+  CASE
+    WHEN level = 0 THEN 'Cantrip'
+    ELSE 'Level ' || level
+  END
+);
+
+UPDATE spells
+SET level = unparsed_levels.level
+FROM unparsed_levels
+WHERE unparsed_levels.spell_id = spells.id;
+
+DROP TABLE IF EXISTS "unparsed_levels";
@@ -0,0 +1,39 @@
+-- Your SQL goes here
+-- legacy levels look like "Cantrip", "Level 3", "3rd level" or just "3"
+CREATE FUNCTION pg_temp.parse_level(level TEXT) RETURNS INT AS $$
+  SELECT CASE
+    WHEN m IS NULL THEN NULL
+    WHEN m[1] IS NULL THEN 0
+    WHEN m[1]::INT BETWEEN 0 AND 9 THEN m[1]::INT
+  END
+  FROM regexp_match(
+    trim(level),
+    '^(?:cantrip|(?:level\s*)?([0-9]{1,2})(?:st|nd|rd|th)?(?:[\s-]*level)?)$',
+    'i'
+  ) AS m
+$$ LANGUAGE SQL;
+
+-- keep everything we couldn't make sense of, so it can be fixed by hand
+CREATE TABLE unparsed_levels (
+  spell_id INT PRIMARY KEY REFERENCES spells(id) ON DELETE CASCADE,
+  level VARCHAR NOT NULL
+);
+
+INSERT INTO unparsed_levels (spell_id, level)
+SELECT id, level FROM spells WHERE pg_temp.parse_level(level) IS NULL;
+
+DO $$
+DECLARE
+  unparsed INT;
+BEGIN
+  SELECT count(*) INTO unparsed FROM unparsed_levels;
+  IF unparsed > 0 THEN
+    RAISE NOTICE '% level(s) could not be parsed and were set to "Cantrip", see table unparsed_levels', unparsed;
+  END IF;
+END $$;
+
+ALTER TABLE spells
+ALTER COLUMN level TYPE INT USING coalesce(pg_temp.parse_level(level), 0);
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_level_range CHECK (level BETWEEN 0 AND 9);
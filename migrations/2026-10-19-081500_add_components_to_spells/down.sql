@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE spells
+DROP COLUMN verbal,
+DROP COLUMN somatic,
+DROP COLUMN material,
+DROP COLUMN material_description,
+DROP COLUMN material_cost,
+DROP COLUMN material_consumed;
@@ -0,0 +1,20 @@
+-- Your SQL goes here
+ALTER TABLE spells
+ADD verbal BOOL NOT NULL DEFAULT false,
+ADD somatic BOOL NOT NULL DEFAULT false,
+ADD material BOOL NOT NULL DEFAULT false,
+ADD material_description TEXT,
+ADD material_cost INT,
+ADD material_consumed BOOL NOT NULL DEFAULT false;
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_material_details CHECK (
+  material OR (
+    material_description IS NULL
+    AND material_cost IS NULL
+    AND NOT material_consumed
+  )
+);
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_material_cost_positive CHECK (material_cost > 0);
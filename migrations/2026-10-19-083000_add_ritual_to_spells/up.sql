@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE spells
+ADD ritual BOOL NOT NULL DEFAULT false;
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+CREATE TABLE spell_classes (
+  spell_id INT NOT NULL REFERENCES spells(id) ON DELETE CASCADE,
+  caster_class VARCHAR NOT NULL,
+  PRIMARY KEY (spell_id, caster_class)
+);
+
+CREATE INDEX spell_classes_caster_class_idx ON spell_classes (caster_class);
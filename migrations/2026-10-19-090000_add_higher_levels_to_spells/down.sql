@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE spells
+DROP COLUMN effect_dice,
+DROP COLUMN higher_levels,
+DROP COLUMN higher_level_dice,
+DROP COLUMN cantrip_scaling;
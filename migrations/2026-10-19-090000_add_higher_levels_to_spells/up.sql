@@ -0,0 +1,22 @@
+-- Your SQL goes here
+ALTER TABLE spells
+ADD effect_dice VARCHAR,
+ADD higher_levels TEXT,
+ADD higher_level_dice VARCHAR,
+ADD cantrip_scaling BOOL NOT NULL DEFAULT false;
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_effect_dice_format
+CHECK (effect_dice ~ '^[1-9][0-9]*d[1-9][0-9]*(\+[0-9]+)?$');
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_higher_level_dice_format
+CHECK (higher_level_dice ~ '^[1-9][0-9]*d[1-9][0-9]*(\+[0-9]+)?$');
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_higher_level_dice_leveled
+CHECK (higher_level_dice IS NULL OR (level > 0 AND effect_dice IS NOT NULL));
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_cantrip_scaling_cantrip
+CHECK (NOT cantrip_scaling OR (level = 0 AND effect_dice IS NOT NULL));
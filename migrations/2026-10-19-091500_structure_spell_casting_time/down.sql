@@ -0,0 +1,24 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE spells
+ADD casting_time VARCHAR;
+
+UPDATE spells
+SET casting_time = casting_time_quantity || ' ' || CASE casting_time_unit
+    WHEN 'BonusAction' THEN 'Bonus Action'
+    ELSE casting_time_unit
+  END
+  || CASE WHEN casting_time_quantity > 1 THEN 's' ELSE '' END
+  || coalesce(', ' || reaction_trigger, '');
+
+UPDATE spells
+SET casting_time = unparsed_casting_times.casting_time
+FROM unparsed_casting_times
+WHERE unparsed_casting_times.spell_id = spells.id;
+
+DROP TABLE IF EXISTS "unparsed_casting_times";
+
+ALTER TABLE spells
+ALTER COLUMN casting_time SET NOT NULL,
+DROP COLUMN casting_time_unit,
+DROP COLUMN casting_time_quantity,
+DROP COLUMN reaction_trigger;
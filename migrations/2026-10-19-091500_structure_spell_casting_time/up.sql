@@ -0,0 +1,88 @@
+-- Your SQL goes here
+ALTER TABLE spells
+ADD casting_time_unit VARCHAR,
+ADD casting_time_quantity INT,
+ADD reaction_trigger TEXT;
+
+CREATE FUNCTION pg_temp.parse_quantity(quantity TEXT) RETURNS INT AS $$
+  SELECT CASE lower(quantity)
+    WHEN 'a' THEN 1
+    WHEN 'an' THEN 1
+    WHEN 'one' THEN 1
+    WHEN 'two' THEN 2
+    WHEN 'three' THEN 3
+    WHEN 'five' THEN 5
+    WHEN 'ten' THEN 10
+    WHEN 'twelve' THEN 12
+    WHEN 'twenty-four' THEN 24
+    ELSE coalesce(quantity, '1')::INT
+  END
+$$ LANGUAGE SQL;
+
+-- legacy casting times look like "1 Action", "one action", "10 minutes" or
+-- "Reaction, which you take when you are hit by an attack"
+WITH parsed AS (
+  SELECT id, regexp_match(
+    trim(casting_time),
+    '^(?:([0-9]+|a|an|one|two|three|five|ten|twelve|twenty-four)\s+)?(action|bonus action|reaction|minute|hour)s?(?:\s*,\s*(.+))?$',
+    'i'
+  ) AS m
+  FROM spells
+)
+UPDATE spells
+SET
+  casting_time_unit = CASE lower(parsed.m[2])
+    WHEN 'action' THEN 'Action'
+    WHEN 'bonus action' THEN 'BonusAction'
+    WHEN 'reaction' THEN 'Reaction'
+    WHEN 'minute' THEN 'Minute'
+    WHEN 'hour' THEN 'Hour'
+  END,
+  casting_time_quantity = pg_temp.parse_quantity(parsed.m[1]),
+  reaction_trigger = parsed.m[3]
+FROM parsed
+WHERE parsed.id = spells.id
+  AND parsed.m IS NOT NULL
+  AND (parsed.m[3] IS NULL OR lower(parsed.m[2]) = 'reaction')
+  AND (pg_temp.parse_quantity(parsed.m[1]) = 1 OR lower(parsed.m[2]) IN ('minute', 'hour'));
+
+-- keep everything we couldn't make sense of, so it can be fixed by hand
+CREATE TABLE unparsed_casting_times (
+  spell_id INT PRIMARY KEY REFERENCES spells(id) ON DELETE CASCADE,
+  casting_time VARCHAR NOT NULL
+);
+
+INSERT INTO unparsed_casting_times (spell_id, casting_time)
+SELECT id, casting_time FROM spells WHERE casting_time_unit IS NULL;
+
+DO $$
+DECLARE
+  unparsed INT;
+BEGIN
+  SELECT count(*) INTO unparsed FROM unparsed_casting_times;
+  IF unparsed > 0 THEN
+    RAISE NOTICE '% casting time(s) could not be parsed and were set to "1 Action", see table unparsed_casting_times', unparsed;
+  END IF;
+END $$;
+
+UPDATE spells
+SET casting_time_unit = 'Action', casting_time_quantity = 1
+WHERE casting_time_unit IS NULL;
+
+ALTER TABLE spells
+ALTER COLUMN casting_time_unit SET NOT NULL,
+ALTER COLUMN casting_time_quantity SET NOT NULL,
+ALTER COLUMN casting_time_quantity SET DEFAULT 1,
+DROP COLUMN casting_time;
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_casting_time_unit
+CHECK (casting_time_unit IN ('Action', 'BonusAction', 'Reaction', 'Minute', 'Hour'));
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_casting_time_quantity
+CHECK (casting_time_quantity = 1 OR (casting_time_quantity > 1 AND casting_time_unit IN ('Minute', 'Hour')));
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_reaction_trigger_reaction
+CHECK (reaction_trigger IS NULL OR casting_time_unit = 'Reaction');
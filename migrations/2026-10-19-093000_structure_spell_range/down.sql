@@ -0,0 +1,30 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE spells
+ADD range VARCHAR;
+
+UPDATE spells
+SET range = CASE range_type
+    WHEN 'Distance' THEN range_distance || ' ' || CASE
+      WHEN range_unit = 'Feet' THEN 'feet'
+      WHEN range_distance = 1 THEN 'mile'
+      ELSE 'miles'
+    END
+    ELSE range_type
+  END
+  || coalesce(' (' || area_size || '-foot ' || lower(area_shape) || ')', '');
+
+UPDATE spells
+SET range = unparsed_ranges.range
+FROM unparsed_ranges
+WHERE unparsed_ranges.spell_id = spells.id;
+
+DROP TABLE IF EXISTS "unparsed_ranges";
+
+ALTER TABLE spells
+ALTER COLUMN range SET NOT NULL,
+DROP COLUMN range_feet,
+DROP COLUMN range_type,
+DROP COLUMN range_distance,
+DROP COLUMN range_unit,
+DROP COLUMN area_shape,
+DROP COLUMN area_size;
@@ -0,0 +1,98 @@
+-- Your SQL goes here
+ALTER TABLE spells
+ADD range_type VARCHAR,
+ADD range_distance INT,
+ADD range_unit VARCHAR,
+ADD area_shape VARCHAR,
+ADD area_size INT;
+
+-- legacy ranges look like "Touch", "150 feet", "30 ft.", "1 mile" or "Self (15-foot cone)"
+WITH parsed AS (
+  SELECT id, regexp_match(
+    trim(range),
+    '^(self|touch|sight|unlimited|special|([0-9]+)\s*(feet|foot|ft\.?|miles?))\s*(?:\(\s*([0-9]+)[- ](?:foot|feet|ft\.?)(?:[- ]radius)?[- ]?(cone|cube|cylinder|line|sphere|emanation|radius)\s*\))?$',
+    'i'
+  ) AS m
+  FROM spells
+)
+UPDATE spells
+SET
+  range_type = CASE
+    WHEN parsed.m[2] IS NOT NULL THEN 'Distance'
+    WHEN lower(parsed.m[1]) = 'self' THEN 'Self'
+    ELSE initcap(parsed.m[1])
+  END,
+  range_distance = parsed.m[2]::INT,
+  range_unit = CASE
+    WHEN parsed.m[3] ILIKE 'mile%' THEN 'Miles'
+    WHEN parsed.m[3] IS NOT NULL THEN 'Feet'
+  END,
+  area_size = parsed.m[4]::INT,
+  area_shape = CASE
+    WHEN lower(parsed.m[5]) = 'radius' AND lower(parsed.m[1]) = 'self' THEN 'Emanation'
+    WHEN lower(parsed.m[5]) = 'radius' THEN 'Sphere'
+    ELSE initcap(parsed.m[5])
+  END
+FROM parsed
+WHERE parsed.id = spells.id
+  AND parsed.m IS NOT NULL;
+
+-- keep everything we couldn't make sense of, so it can be fixed by hand
+CREATE TABLE unparsed_ranges (
+  spell_id INT PRIMARY KEY REFERENCES spells(id) ON DELETE CASCADE,
+  range VARCHAR NOT NULL
+);
+
+INSERT INTO unparsed_ranges (spell_id, range)
+SELECT id, range FROM spells WHERE range_type IS NULL;
+
+DO $$
+DECLARE
+  unparsed INT;
+BEGIN
+  SELECT count(*) INTO unparsed FROM unparsed_ranges;
+  IF unparsed > 0 THEN
+    RAISE NOTICE '% range(s) could not be parsed and were set to "Special", see table unparsed_ranges', unparsed;
+  END IF;
+END $$;
+
+UPDATE spells
+SET range_type = 'Special'
+WHERE range_type IS NULL;
+
+ALTER TABLE spells
+ALTER COLUMN range_type SET NOT NULL,
+DROP COLUMN range;
+
+-- distances in feet, so ranges can be compared regardless of their unit
+ALTER TABLE spells
+ADD range_feet INT GENERATED ALWAYS AS (
+  CASE range_unit
+    WHEN 'Miles' THEN range_distance * 5280
+    ELSE range_distance
+  END
+) STORED;
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_range_type
+CHECK (range_type IN ('Self', 'Touch', 'Distance', 'Sight', 'Unlimited', 'Special'));
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_range_unit
+CHECK (range_unit IN ('Feet', 'Miles'));
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_range_distance
+CHECK (
+  (range_type = 'Distance') = (range_distance IS NOT NULL AND range_unit IS NOT NULL)
+  AND (range_type = 'Distance' OR (range_distance IS NULL AND range_unit IS NULL))
+  AND range_distance > 0
+);
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_area_shape
+CHECK (area_shape IN ('Cone', 'Cube', 'Cylinder', 'Line', 'Sphere', 'Emanation'));
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_area
+CHECK ((area_shape IS NULL) = (area_size IS NULL) AND area_size > 0);
@@ -0,0 +1,30 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE spells
+ADD duration VARCHAR;
+
+UPDATE spells
+SET duration = CASE
+    WHEN concentration AND duration_up_to THEN 'Concentration, up to '
+    WHEN duration_up_to THEN 'Up to '
+    ELSE ''
+  END
+  || CASE duration_unit
+    WHEN 'UntilDispelled' THEN 'Until dispelled'
+    WHEN 'Instantaneous' THEN 'Instantaneous'
+    WHEN 'Special' THEN 'Special'
+    ELSE duration_quantity || ' ' || lower(duration_unit) || CASE WHEN duration_quantity = 1 THEN '' ELSE 's' END
+  END;
+
+UPDATE spells
+SET duration = unparsed_durations.duration
+FROM unparsed_durations
+WHERE unparsed_durations.spell_id = spells.id;
+
+DROP TABLE IF EXISTS "unparsed_durations";
+
+ALTER TABLE spells
+ALTER COLUMN duration SET NOT NULL,
+DROP COLUMN duration_seconds,
+DROP COLUMN duration_unit,
+DROP COLUMN duration_quantity,
+DROP COLUMN duration_up_to;
@@ -0,0 +1,92 @@
+-- Your SQL goes here
+ALTER TABLE spells
+ADD duration_unit VARCHAR,
+ADD duration_quantity INT,
+ADD duration_up_to BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- legacy durations look like "Instantaneous", "1 round", "Up to 8 hours" or "Concentration, up to 1 minute"
+WITH parsed AS (
+  SELECT id, regexp_match(
+    trim(duration),
+    '^(concentration,\s*)?(up to\s+)?(?:(instantaneous|until dispelled|special)|([0-9]+)\s*(round|minute|hour|day)s?)$',
+    'i'
+  ) AS m
+  FROM spells
+)
+UPDATE spells
+SET
+  duration_unit = CASE
+    WHEN parsed.m[4] IS NOT NULL THEN initcap(parsed.m[5])
+    WHEN lower(parsed.m[3]) = 'until dispelled' THEN 'UntilDispelled'
+    ELSE initcap(parsed.m[3])
+  END,
+  duration_quantity = parsed.m[4]::INT,
+  duration_up_to = parsed.m[2] IS NOT NULL,
+  concentration = spells.concentration OR parsed.m[1] IS NOT NULL
+FROM parsed
+WHERE parsed.id = spells.id
+  AND parsed.m IS NOT NULL
+  -- "up to" only makes sense for timed durations
+  AND (parsed.m[2] IS NULL OR parsed.m[4] IS NOT NULL)
+  -- a concentration spell can't be instantaneous
+  AND NOT (
+    (spells.concentration OR parsed.m[1] IS NOT NULL)
+    AND lower(parsed.m[3]) IS NOT DISTINCT FROM 'instantaneous'
+  );
+
+-- keep everything we couldn't make sense of, so it can be fixed by hand
+CREATE TABLE unparsed_durations (
+  spell_id INT PRIMARY KEY REFERENCES spells(id) ON DELETE CASCADE,
+  duration VARCHAR NOT NULL
+);
+
+INSERT INTO unparsed_durations (spell_id, duration)
+SELECT id, duration FROM spells WHERE duration_unit IS NULL;
+
+DO $$
+DECLARE
+  unparsed INT;
+BEGIN
+  SELECT count(*) INTO unparsed FROM unparsed_durations;
+  IF unparsed > 0 THEN
+    RAISE NOTICE '% duration(s) could not be parsed and were set to "Special", see table unparsed_durations', unparsed;
+  END IF;
+END $$;
+
+UPDATE spells
+SET duration_unit = 'Special'
+WHERE duration_unit IS NULL;
+
+ALTER TABLE spells
+ALTER COLUMN duration_unit SET NOT NULL,
+DROP COLUMN duration;
+
+-- durations in seconds, so durations can be compared regardless of their unit
+ALTER TABLE spells
+ADD duration_seconds INT GENERATED ALWAYS AS (
+  duration_quantity * CASE duration_unit
+    WHEN 'Round' THEN 6
+    WHEN 'Minute' THEN 60
+    WHEN 'Hour' THEN 3600
+    WHEN 'Day' THEN 86400
+  END
+) STORED;
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_duration_unit
+CHECK (duration_unit IN ('Instantaneous', 'Round', 'Minute', 'Hour', 'Day', 'UntilDispelled', 'Special'));
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_duration_quantity
+CHECK (
+  (duration_unit IN ('Round', 'Minute', 'Hour', 'Day')) = (duration_quantity IS NOT NULL)
+  AND duration_quantity > 0
+);
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_duration_up_to
+CHECK (NOT duration_up_to OR duration_quantity IS NOT NULL);
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_concentration_duration
+CHECK (NOT (concentration AND duration_unit = 'Instantaneous'));
@@ -0,0 +1,27 @@
+-- This file should undo anything in `up.sql`
+UPDATE spells
+SET damage_dice = healing_dice
+WHERE damage_dice IS NULL;
+
+ALTER TABLE spells
+DROP CONSTRAINT spells_higher_level_dice_leveled,
+ADD CONSTRAINT spells_higher_level_dice_leveled
+CHECK (higher_level_dice IS NULL OR (level > 0 AND damage_dice IS NOT NULL));
+
+ALTER TABLE spells
+DROP CONSTRAINT spells_cantrip_scaling_cantrip,
+ADD CONSTRAINT spells_cantrip_scaling_cantrip
+CHECK (NOT cantrip_scaling OR (level = 0 AND damage_dice IS NOT NULL));
+
+ALTER TABLE spells
+DROP COLUMN healing_dice,
+DROP COLUMN damage_types,
+DROP COLUMN save_ability,
+DROP COLUMN attack_type,
+DROP COLUMN conditions;
+
+ALTER TABLE spells
+RENAME CONSTRAINT spells_damage_dice_format TO spells_effect_dice_format;
+
+ALTER TABLE spells
+RENAME COLUMN damage_dice TO effect_dice;
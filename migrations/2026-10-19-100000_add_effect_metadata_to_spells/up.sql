@@ -0,0 +1,81 @@
+-- Your SQL goes here
+ALTER TABLE spells
+RENAME COLUMN effect_dice TO damage_dice;
+
+ALTER TABLE spells
+RENAME CONSTRAINT spells_effect_dice_format TO spells_damage_dice_format;
+
+ALTER TABLE spells
+ADD healing_dice VARCHAR,
+ADD damage_types TEXT[] NOT NULL DEFAULT '{}',
+ADD save_ability VARCHAR,
+ADD attack_type VARCHAR,
+ADD conditions TEXT[] NOT NULL DEFAULT '{}';
+
+-- the effect dice of spells restoring hit points are healing dice
+UPDATE spells
+SET healing_dice = damage_dice, damage_dice = NULL
+WHERE damage_dice IS NOT NULL
+  AND description ~* 'regains? [^.]*hit points'
+  AND description !~* 'damage';
+
+-- fill in what the description clearly states, conditions are too ambiguous to guess
+UPDATE spells
+SET damage_types = ARRAY(
+  SELECT damage_type
+  FROM unnest(ARRAY[
+    'Acid', 'Bludgeoning', 'Cold', 'Fire', 'Force', 'Lightning', 'Necrotic',
+    'Piercing', 'Poison', 'Psychic', 'Radiant', 'Slashing', 'Thunder'
+  ]::TEXT[]) AS damage_type
+  WHERE description ~* ('\m' || damage_type || ' damage')
+);
+
+UPDATE spells
+SET save_ability = initcap((regexp_match(
+  description,
+  '\m(strength|dexterity|constitution|intelligence|wisdom|charisma) saving throw',
+  'i'
+))[1]);
+
+UPDATE spells
+SET attack_type = initcap((regexp_match(description, '\m(melee|ranged) spell attack', 'i'))[1]);
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_healing_dice_format
+CHECK (healing_dice ~ '^[1-9][0-9]*d[1-9][0-9]*(\+[0-9]+)?$');
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_damage_types
+CHECK (damage_types <@ ARRAY[
+  'Acid', 'Bludgeoning', 'Cold', 'Fire', 'Force', 'Lightning', 'Necrotic',
+  'Piercing', 'Poison', 'Psychic', 'Radiant', 'Slashing', 'Thunder'
+]::TEXT[]);
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_save_ability
+CHECK (save_ability IN ('Strength', 'Dexterity', 'Constitution', 'Intelligence', 'Wisdom', 'Charisma'));
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_attack_type
+CHECK (attack_type IN ('Melee', 'Ranged'));
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_conditions
+CHECK (conditions <@ ARRAY[
+  'Blinded', 'Charmed', 'Deafened', 'Exhaustion', 'Frightened', 'Grappled', 'Incapacitated',
+  'Invisible', 'Paralyzed', 'Petrified', 'Poisoned', 'Prone', 'Restrained', 'Stunned', 'Unconscious'
+]::TEXT[]);
+
+-- higher levels and cantrip scaling apply to damage and healing dice alike
+ALTER TABLE spells
+DROP CONSTRAINT spells_higher_level_dice_leveled,
+ADD CONSTRAINT spells_higher_level_dice_leveled
+CHECK (higher_level_dice IS NULL OR (level > 0 AND (damage_dice IS NOT NULL OR healing_dice IS NOT NULL)));
+
+ALTER TABLE spells
+DROP CONSTRAINT spells_cantrip_scaling_cantrip,
+ADD CONSTRAINT spells_cantrip_scaling_cantrip
+CHECK (NOT cantrip_scaling OR (level = 0 AND (damage_dice IS NOT NULL OR healing_dice IS NOT NULL)));
+
+CREATE INDEX spells_damage_types_idx ON spells USING GIN (damage_types);
+CREATE INDEX spells_conditions_idx ON spells USING GIN (conditions);
@@ -0,0 +1,11 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER IF EXISTS set_updated_at ON spells;
+DROP TRIGGER IF EXISTS set_updated_at ON users;
+
+ALTER TABLE spells
+DROP COLUMN created_at,
+DROP COLUMN updated_at;
+
+ALTER TABLE users
+DROP COLUMN created_at,
+DROP COLUMN updated_at;
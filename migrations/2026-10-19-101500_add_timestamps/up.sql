@@ -0,0 +1,15 @@
+-- Your SQL goes here
+-- existing rows are backfilled with the time of the migration
+ALTER TABLE spells
+ADD created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+ADD updated_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+ALTER TABLE users
+ADD created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+ADD updated_at TIMESTAMPTZ NOT NULL DEFAULT now();
+
+SELECT diesel_manage_updated_at('spells');
+SELECT diesel_manage_updated_at('users');
+
+CREATE INDEX spells_created_at_idx ON spells (created_at);
+CREATE INDEX spells_updated_at_idx ON spells (updated_at);
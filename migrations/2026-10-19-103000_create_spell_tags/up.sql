@@ -0,0 +1,9 @@
+-- Your SQL goes here
+CREATE TABLE spell_tags (
+  spell_id INT NOT NULL REFERENCES spells(id) ON DELETE CASCADE,
+  tag VARCHAR NOT NULL,
+  PRIMARY KEY (spell_id, tag),
+  CONSTRAINT spell_tags_tag CHECK (tag = btrim(tag) AND char_length(tag) BETWEEN 1 AND 50)
+);
+
+CREATE INDEX spell_tags_tag_idx ON spell_tags (tag);
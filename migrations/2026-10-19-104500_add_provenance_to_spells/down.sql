@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE spells
+DROP COLUMN source,
+DROP COLUMN page,
+DROP COLUMN is_homebrew,
+DROP COLUMN license,
+DROP COLUMN license_label;
@@ -0,0 +1,26 @@
+-- Your SQL goes here
+ALTER TABLE spells
+ADD source VARCHAR,
+ADD page INT,
+ADD is_homebrew BOOLEAN NOT NULL DEFAULT FALSE,
+ADD license VARCHAR,
+ADD license_label VARCHAR;
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_page
+CHECK (page IS NULL OR (page > 0 AND source IS NOT NULL));
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_license
+CHECK (license IN ('CC0-1.0', 'CC-BY-4.0', 'CC-BY-SA-4.0', 'OGL-1.0a', 'AllRightsReserved', 'Custom'));
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_license_label
+CHECK ((license = 'Custom') = (license_label IS NOT NULL));
+
+-- spells under a license forbidding redistribution can't be shared
+ALTER TABLE spells
+ADD CONSTRAINT spells_published_license
+CHECK (NOT published OR license IS NULL OR license NOT IN ('AllRightsReserved', 'Custom'));
+
+CREATE INDEX spells_source_idx ON spells (source);
@@ -0,0 +1,12 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE spells
+ALTER COLUMN magic_school TYPE VARCHAR USING magic_school::TEXT;
+
+UPDATE spells
+SET magic_school = unparsed_magic_schools.magic_school
+FROM unparsed_magic_schools
+WHERE unparsed_magic_schools.spell_id = spells.id;
+
+DROP TABLE IF EXISTS "unparsed_magic_schools";
+
+DROP TYPE magic_school;
@@ -0,0 +1,41 @@
+-- Your SQL goes here
+CREATE TYPE magic_school AS ENUM (
+  'Abjuration',
+  'Conjuration',
+  'Divination',
+  'Enchantment',
+  'Evocation',
+  'Illusion',
+  'Necromancy',
+  'Transmutation'
+);
+
+-- keep every school that isn't one of the above, so it can be fixed by hand
+CREATE TABLE unparsed_magic_schools (
+  spell_id INT PRIMARY KEY REFERENCES spells(id) ON DELETE CASCADE,
+  magic_school VARCHAR NOT NULL
+);
+
+INSERT INTO unparsed_magic_schools (spell_id, magic_school)
+SELECT id, magic_school
+FROM spells
+WHERE initcap(trim(magic_school)) NOT IN (SELECT unnest(enum_range(NULL::magic_school))::TEXT);
+
+DO $$
+DECLARE
+  unparsed INT;
+BEGIN
+  SELECT count(*) INTO unparsed FROM unparsed_magic_schools;
+  IF unparsed > 0 THEN
+    RAISE NOTICE '% magic school(s) could not be parsed and were set to "Evocation", see table unparsed_magic_schools', unparsed;
+  END IF;
+END $$;
+
+UPDATE spells
+SET magic_school = CASE
+    WHEN id IN (SELECT spell_id FROM unparsed_magic_schools) THEN 'Evocation'
+    ELSE initcap(trim(magic_school))
+  END;
+
+ALTER TABLE spells
+ALTER COLUMN magic_school TYPE magic_school USING magic_school::magic_school;
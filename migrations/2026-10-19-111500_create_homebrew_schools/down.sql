@@ -0,0 +1,18 @@
+-- This file should undo anything in `up.sql`
+INSERT INTO unparsed_magic_schools (spell_id, magic_school)
+SELECT spells.id, homebrew_schools.name
+FROM spells
+JOIN homebrew_schools ON homebrew_schools.id = spells.homebrew_school_id;
+
+ALTER TABLE spells
+DROP CONSTRAINT spells_magic_school;
+
+UPDATE spells
+SET magic_school = 'Evocation'
+WHERE homebrew_school_id IS NOT NULL;
+
+ALTER TABLE spells
+DROP COLUMN homebrew_school_id,
+ALTER COLUMN magic_school SET NOT NULL;
+
+DROP TABLE homebrew_schools;
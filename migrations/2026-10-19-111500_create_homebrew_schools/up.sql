@@ -0,0 +1,44 @@
+-- Your SQL goes here
+CREATE TABLE homebrew_schools (
+  id SERIAL PRIMARY KEY,
+  user_id INT NOT NULL REFERENCES users(id),
+  name VARCHAR NOT NULL,
+  description TEXT,
+  shared BOOLEAN NOT NULL DEFAULT FALSE,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  CONSTRAINT homebrew_schools_name CHECK (char_length(trim(name)) BETWEEN 1 AND 50)
+);
+
+CREATE UNIQUE INDEX homebrew_schools_user_name ON homebrew_schools (user_id, lower(name));
+
+SELECT diesel_manage_updated_at('homebrew_schools');
+
+-- a spell belongs to exactly one school, either a standard or a homebrew one
+ALTER TABLE spells
+ADD COLUMN homebrew_school_id INT REFERENCES homebrew_schools(id),
+ALTER COLUMN magic_school DROP NOT NULL;
+
+ALTER TABLE spells
+ADD CONSTRAINT spells_magic_school CHECK ((magic_school IS NULL) <> (homebrew_school_id IS NULL));
+
+CREATE INDEX spells_homebrew_school_id ON spells (homebrew_school_id);
+
+-- the schools that couldn't be parsed become homebrew schools of the spells' authors
+INSERT INTO homebrew_schools (user_id, name)
+SELECT DISTINCT ON (spells.user_id, lower(trim(unparsed_magic_schools.magic_school)))
+  spells.user_id, trim(unparsed_magic_schools.magic_school)
+FROM unparsed_magic_schools
+JOIN spells ON spells.id = unparsed_magic_schools.spell_id
+WHERE char_length(trim(unparsed_magic_schools.magic_school)) BETWEEN 1 AND 50
+ORDER BY spells.user_id, lower(trim(unparsed_magic_schools.magic_school)), spells.id;
+
+UPDATE spells
+SET magic_school = NULL, homebrew_school_id = homebrew_schools.id
+FROM unparsed_magic_schools, homebrew_schools
+WHERE unparsed_magic_schools.spell_id = spells.id
+  AND homebrew_schools.user_id = spells.user_id
+  AND lower(homebrew_schools.name) = lower(trim(unparsed_magic_schools.magic_school));
+
+DELETE FROM unparsed_magic_schools
+WHERE spell_id IN (SELECT id FROM spells WHERE homebrew_school_id IS NOT NULL);
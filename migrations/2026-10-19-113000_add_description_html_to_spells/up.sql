@@ -0,0 +1,5 @@
+-- Your SQL goes here
+-- rendered from the markdown description whenever it is saved, existing descriptions are
+-- rendered on read until their spell is saved again
+ALTER TABLE spells
+ADD COLUMN description_html TEXT;
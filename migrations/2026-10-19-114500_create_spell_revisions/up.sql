@@ -0,0 +1,34 @@
+-- Your SQL goes here
+CREATE TABLE spell_revisions (
+  id SERIAL PRIMARY KEY,
+  spell_id INT NOT NULL REFERENCES spells(id) ON DELETE CASCADE,
+  revision INT NOT NULL,
+  user_id INT NOT NULL REFERENCES users(id),
+  snapshot JSONB NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+  CONSTRAINT spell_revisions_revision UNIQUE (spell_id, revision)
+);
+
+-- the current state of every spell becomes its first revision
+INSERT INTO spell_revisions (spell_id, revision, user_id, snapshot, created_at)
+SELECT
+  spells.id,
+  1,
+  spells.user_id,
+  to_jsonb(spells)
+    - 'id' - 'user_id' - 'published' - 'nanoid' - 'created_at' - 'updated_at'
+    - 'range_feet' - 'duration_seconds'
+    || jsonb_build_object(
+      'magic_school', coalesce(spells.magic_school::TEXT, homebrew_schools.name),
+      'classes', coalesce(
+        (SELECT jsonb_agg(caster_class ORDER BY caster_class) FROM spell_classes WHERE spell_id = spells.id),
+        '[]'::jsonb
+      ),
+      'tags', coalesce(
+        (SELECT jsonb_agg(tag ORDER BY tag) FROM spell_tags WHERE spell_id = spells.id),
+        '[]'::jsonb
+      )
+    ),
+  spells.updated_at
+FROM spells
+LEFT JOIN homebrew_schools ON homebrew_schools.id = spells.homebrew_school_id;
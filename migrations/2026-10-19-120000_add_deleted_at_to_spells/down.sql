@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DELETE FROM spells
+WHERE deleted_at IS NOT NULL;
+
+ALTER TABLE spells
+DROP COLUMN deleted_at;
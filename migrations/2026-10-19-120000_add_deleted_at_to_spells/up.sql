@@ -0,0 +1,9 @@
+-- Your SQL goes here
+ALTER TABLE spells
+ADD COLUMN deleted_at TIMESTAMPTZ;
+
+-- trashed spells can't stay public
+ALTER TABLE spells
+ADD CONSTRAINT spells_published_not_deleted CHECK (deleted_at IS NULL OR NOT published);
+
+CREATE INDEX spells_deleted_at ON spells (deleted_at) WHERE deleted_at IS NOT NULL;
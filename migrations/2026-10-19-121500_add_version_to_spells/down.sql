@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER bump_spell_version ON spells;
+DROP FUNCTION spells_bump_version();
+
+ALTER TABLE spells
+DROP COLUMN version;
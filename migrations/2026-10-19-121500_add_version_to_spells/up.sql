@@ -0,0 +1,15 @@
+-- Your SQL goes here
+ALTER TABLE spells
+ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+
+-- every write to a spell gets a new version, so clients can tell if their copy is outdated,
+-- the version in the written values is ignored
+CREATE OR REPLACE FUNCTION spells_bump_version() RETURNS trigger AS $$
+BEGIN
+    NEW.version := OLD.version + 1;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER bump_spell_version BEFORE UPDATE ON spells
+    FOR EACH ROW EXECUTE PROCEDURE spells_bump_version();
@@ -0,0 +1,4 @@
+-- Your SQL goes here
+-- the content addressed file name of the illustration, copies of a spell share the file
+ALTER TABLE spells
+ADD COLUMN image VARCHAR;
@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP FUNCTION spell_copy_count;
+DROP FUNCTION spell_school_name;
+
+ALTER TABLE spells
+DROP COLUMN copied_from_id;
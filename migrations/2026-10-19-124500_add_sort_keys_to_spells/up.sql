@@ -0,0 +1,19 @@
+-- Your SQL goes here
+ALTER TABLE spells
+ADD COLUMN copied_from_id INT REFERENCES spells(id) ON DELETE SET NULL;
+
+CREATE INDEX spells_copied_from_id ON spells (copied_from_id);
+
+-- the name of the standard or homebrew school of a spell, as spells are sorted by it
+CREATE FUNCTION spell_school_name(magic_school magic_school, homebrew_school_id INT)
+RETURNS VARCHAR AS $$
+    SELECT COALESCE(
+        magic_school::VARCHAR,
+        (SELECT name FROM homebrew_schools WHERE id = homebrew_school_id)
+    );
+$$ LANGUAGE SQL STABLE;
+
+-- how popular a published spell is, by the number of times it was copied
+CREATE FUNCTION spell_copy_count(spell_id INT) RETURNS BIGINT AS $$
+    SELECT COUNT(*) FROM spells WHERE copied_from_id = spell_id;
+$$ LANGUAGE SQL STABLE;
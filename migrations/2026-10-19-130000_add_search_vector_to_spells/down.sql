@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+DROP FUNCTION spell_search_headline;
+DROP FUNCTION spell_search_query;
+
+ALTER TABLE spells
+DROP COLUMN search_vector;
+
+DROP TEXT SEARCH CONFIGURATION spellbook;
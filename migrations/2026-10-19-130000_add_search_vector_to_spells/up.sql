@@ -0,0 +1,28 @@
+-- Your SQL goes here
+-- spells are searched in English, change the mappings of this configuration to search them in
+-- another language. The stored search vectors are not recomputed when the configuration changes,
+-- so a migration changing it has to drop and add search_vector again to index all spells anew
+CREATE TEXT SEARCH CONFIGURATION spellbook (COPY = pg_catalog.english);
+
+-- matches in the name rank higher than matches in the description
+ALTER TABLE spells
+ADD COLUMN search_vector TSVECTOR GENERATED ALWAYS AS (
+    setweight(to_tsvector('spellbook', name), 'A')
+        || setweight(to_tsvector('spellbook', description), 'B')
+) STORED;
+
+CREATE INDEX spells_search_vector ON spells USING GIN (search_vector);
+
+-- searches are written like web searches: words, "quoted phrases", or and -excluded words
+CREATE FUNCTION spell_search_query(search TEXT) RETURNS TSQUERY AS $$
+    SELECT websearch_to_tsquery('spellbook', search);
+$$ LANGUAGE SQL IMMUTABLE;
+
+CREATE FUNCTION spell_search_headline(document TEXT, query TSQUERY) RETURNS TEXT AS $$
+    SELECT ts_headline(
+        'spellbook',
+        document,
+        query,
+        'StartSel=<mark>, StopSel=</mark>, MaxFragments=2, FragmentDelimiter=" … "'
+    );
+$$ LANGUAGE SQL IMMUTABLE;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX spells_name_trigrams;
+
+DROP EXTENSION IF EXISTS pg_trgm;
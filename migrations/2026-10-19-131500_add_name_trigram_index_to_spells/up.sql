@@ -0,0 +1,5 @@
+-- Your SQL goes here
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- trigrams find the names closest to misspelled or partly typed ones
+CREATE INDEX spells_name_trigrams ON spells USING GIN (name gin_trgm_ops);
@@ -0,0 +1,156 @@
+use std::{fmt, str::FromStr};
+
+use regex::Regex;
+
+/// A dice expression like `8d6` or `1d4+1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dice {
+    pub count: u32,
+    pub sides: u32,
+    pub modifier: u32,
+}
+
+#[derive(Debug)]
+pub struct ParseDiceError;
+
+impl FromStr for Dice {
+    type Err = ParseDiceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let dice_regex = Regex::new(r"^([1-9][0-9]*)d([1-9][0-9]*)(?:\+([0-9]+))?$").unwrap();
+        let captures = dice_regex.captures(s).ok_or(ParseDiceError)?;
+
+        let parse = |i: usize| -> Result<u32, ParseDiceError> {
+            match captures.get(i) {
+                Some(m) => m.as_str().parse().map_err(|_| ParseDiceError),
+                None => Ok(0),
+            }
+        };
+
+        Ok(Dice {
+            count: parse(1)?,
+            sides: parse(2)?,
+            modifier: parse(3)?,
+        })
+    }
+}
+
+impl fmt::Display for Dice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}d{}", self.count, self.sides)?;
+        if self.modifier > 0 {
+            write!(f, "+{}", self.modifier)?;
+        }
+        Ok(())
+    }
+}
+
+impl Dice {
+    /// Adds `increment` to the dice `times` times, e.g. `8d6` plus two times `1d6` is `10d6`.
+    /// Returns `None` if the increment rolls a different die or there are too many dice to count.
+    pub fn increased_by(self, increment: Dice, times: u32) -> Option<Dice> {
+        if increment.sides != self.sides {
+            return None;
+        }
+        Some(Dice {
+            count: self
+                .count
+                .checked_add(increment.count.checked_mul(times)?)?,
+            sides: self.sides,
+            modifier: self
+                .modifier
+                .checked_add(increment.modifier.checked_mul(times)?)?,
+        })
+    }
+
+    /// Multiplies the number of dice rolled, e.g. `1d10` times three is `3d10`. Returns `None`
+    /// if there are too many dice to count.
+    pub fn multiplied_by(self, factor: u32) -> Option<Dice> {
+        Some(Dice {
+            count: self.count.checked_mul(factor)?,
+            sides: self.sides,
+            modifier: self.modifier.checked_mul(factor)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dice(count: u32, sides: u32, modifier: u32) -> Dice {
+        Dice {
+            count,
+            sides,
+            modifier,
+        }
+    }
+
+    #[test]
+    fn parses_dice() {
+        assert_eq!("8d6".parse::<Dice>().unwrap(), dice(8, 6, 0));
+        assert_eq!("1d4+1".parse::<Dice>().unwrap(), dice(1, 4, 1));
+        assert_eq!("2d10+0".parse::<Dice>().unwrap(), dice(2, 10, 0));
+    }
+
+    #[test]
+    fn rejects_malformed_dice() {
+        for dice in [
+            "", "d6", "0d6", "1d0", "1d6-1", "1d6+", " 1d6", "1D6", "1d6+1d4",
+        ] {
+            assert!(dice.parse::<Dice>().is_err(), "{dice}");
+        }
+    }
+
+    #[test]
+    fn rejects_dice_too_large_to_count() {
+        assert!("4294967296d6".parse::<Dice>().is_err());
+        assert!("1d6+4294967296".parse::<Dice>().is_err());
+    }
+
+    #[test]
+    fn displays_dice_as_parsed() {
+        assert_eq!(dice(8, 6, 0).to_string(), "8d6");
+        assert_eq!(dice(1, 4, 1).to_string(), "1d4+1");
+    }
+
+    #[test]
+    fn increases_dice() {
+        assert_eq!(
+            dice(8, 6, 0).increased_by(dice(1, 6, 0), 2),
+            Some(dice(10, 6, 0))
+        );
+        assert_eq!(
+            dice(1, 4, 1).increased_by(dice(1, 4, 1), 3),
+            Some(dice(4, 4, 4))
+        );
+        assert_eq!(
+            dice(8, 6, 0).increased_by(dice(1, 6, 0), 0),
+            Some(dice(8, 6, 0))
+        );
+    }
+
+    #[test]
+    fn does_not_increase_dice_by_other_dice() {
+        assert_eq!(dice(8, 6, 0).increased_by(dice(1, 8, 0), 1), None);
+    }
+
+    #[test]
+    fn does_not_increase_dice_beyond_counting() {
+        assert_eq!(dice(1, 6, 0).increased_by(dice(u32::MAX, 6, 0), 2), None);
+        assert_eq!(dice(u32::MAX, 6, 0).increased_by(dice(1, 6, 0), 1), None);
+        assert_eq!(dice(1, 6, 1).increased_by(dice(1, 6, u32::MAX), 1), None);
+    }
+
+    #[test]
+    fn multiplies_dice() {
+        assert_eq!(dice(1, 10, 0).multiplied_by(3), Some(dice(3, 10, 0)));
+        assert_eq!(dice(2, 4, 1).multiplied_by(2), Some(dice(4, 4, 2)));
+    }
+
+    #[test]
+    fn does_not_multiply_dice_beyond_counting() {
+        assert_eq!(dice(u32::MAX, 6, 0).multiplied_by(2), None);
+        assert_eq!(dice(1, 6, u32::MAX).multiplied_by(2), None);
+    }
+}
@@ -1,6 +1,22 @@
-use strum::{EnumString, VariantNames};
+use std::{io::Write, str::FromStr};
 
-#[derive(EnumString, VariantNames)]
+use diesel::{
+    deserialize::{self, FromSql, FromSqlRow},
+    expression::AsExpression,
+    pg::{Pg, PgValue},
+    serialize::{self, IsNull, Output, ToSql},
+};
+use serde::{de, Deserialize, Deserializer};
+use strum::{Display, EnumString, VariantNames};
+
+use crate::{errors::SpellValidationError, schema::sql_types};
+
+/// Stored as the `magic_school` Postgres enum, parsed case insensitively.
+#[derive(
+    EnumString, VariantNames, Display, AsExpression, FromSqlRow, Debug, Clone, Copy, PartialEq,
+)]
+#[strum(ascii_case_insensitive)]
+#[diesel(sql_type = sql_types::MagicSchool)]
 pub enum MagicSchool {
     Abjuration,
     Conjuration,
@@ -12,3 +28,159 @@ pub enum MagicSchool {
     Transmutation,
 }
 
+impl ToSql<sql_types::MagicSchool, Pg> for MagicSchool {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.to_string().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<sql_types::MagicSchool, Pg> for MagicSchool {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        let magic_school = std::str::from_utf8(bytes.as_bytes())?;
+        MagicSchool::from_str(magic_school)
+            .map_err(|_| SpellValidationError::InvalidMagicSchool(magic_school.to_string()).into())
+    }
+}
+
+impl<'de> Deserialize<'de> for MagicSchool {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let magic_school = String::deserialize(deserializer)?;
+        MagicSchool::from_str(&magic_school)
+            .map_err(|_| de::Error::custom(SpellValidationError::InvalidMagicSchool(magic_school)))
+    }
+}
+
+#[derive(EnumString, VariantNames)]
+pub enum DamageType {
+    Acid,
+    Bludgeoning,
+    Cold,
+    Fire,
+    Force,
+    Lightning,
+    Necrotic,
+    Piercing,
+    Poison,
+    Psychic,
+    Radiant,
+    Slashing,
+    Thunder,
+}
+
+#[derive(EnumString, VariantNames)]
+pub enum Ability {
+    Strength,
+    Dexterity,
+    Constitution,
+    Intelligence,
+    Wisdom,
+    Charisma,
+}
+
+#[derive(EnumString, VariantNames)]
+pub enum AttackType {
+    Melee,
+    Ranged,
+}
+
+#[derive(EnumString, VariantNames)]
+pub enum Condition {
+    Blinded,
+    Charmed,
+    Deafened,
+    Exhaustion,
+    Frightened,
+    Grappled,
+    Incapacitated,
+    Invisible,
+    Paralyzed,
+    Petrified,
+    Poisoned,
+    Prone,
+    Restrained,
+    Stunned,
+    Unconscious,
+}
+
+#[derive(EnumString, VariantNames)]
+pub enum CastingTimeUnit {
+    Action,
+    BonusAction,
+    Reaction,
+    Minute,
+    Hour,
+}
+
+#[derive(EnumString, VariantNames, PartialEq)]
+pub enum RangeType {
+    #[strum(serialize = "Self")]
+    Personal,
+    Touch,
+    Distance,
+    Sight,
+    Unlimited,
+    Special,
+}
+
+#[derive(EnumString, VariantNames)]
+pub enum DistanceUnit {
+    Feet,
+    Miles,
+}
+
+#[derive(EnumString, VariantNames)]
+pub enum AreaShape {
+    Cone,
+    Cube,
+    Cylinder,
+    Line,
+    Sphere,
+    Emanation,
+}
+
+#[derive(EnumString, VariantNames, PartialEq)]
+pub enum DurationUnit {
+    Instantaneous,
+    Round,
+    Minute,
+    Hour,
+    Day,
+    UntilDispelled,
+    Special,
+}
+
+#[derive(EnumString, VariantNames)]
+pub enum License {
+    #[strum(serialize = "CC0-1.0")]
+    Cc0,
+    #[strum(serialize = "CC-BY-4.0")]
+    CcBy,
+    #[strum(serialize = "CC-BY-SA-4.0")]
+    CcBySa,
+    #[strum(serialize = "OGL-1.0a")]
+    Ogl,
+    AllRightsReserved,
+    Custom,
+}
+
+impl License {
+    /// Custom licenses can't be checked, so they are treated as forbidding redistribution.
+    pub fn allows_redistribution(&self) -> bool {
+        !matches!(self, License::AllRightsReserved | License::Custom)
+    }
+}
+
+#[derive(EnumString, VariantNames)]
+pub enum CasterClass {
+    Artificer,
+    Bard,
+    Cleric,
+    Druid,
+    Paladin,
+    Ranger,
+    Sorcerer,
+    Warlock,
+    Wizard,
+}
+
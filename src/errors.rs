@@ -1,17 +1,217 @@
+use diesel::result::DatabaseErrorKind;
 use strum::VariantNames;
 use thiserror::Error;
 
-use crate::enums::MagicSchool;
+use crate::{
+    enums::{
+        Ability, AreaShape, AttackType, CasterClass, CastingTimeUnit, Condition, DamageType,
+        DistanceUnit, DurationUnit, License, MagicSchool, RangeType,
+    },
+    requests::{filters::FilterField, spells::SortBy},
+};
 
 #[derive(Debug, Error)]
 pub enum SpellValidationError {
-    #[error("Invalid spell level \"{0}\" expected \"Cantrip\" or \"Level [1-9]\"")]
-    InvalidSpellLevel(String),
+    #[error("Invalid spell level \"{0}\" expected 0 (Cantrip) to 9")]
+    InvalidSpellLevel(i32),
     #[error(
-        "Invalid school of magic \"{0}\" expected one of: {:?}",
+        "Invalid school of magic \"{0}\" expected one of: {:?} or one of your homebrew schools",
         MagicSchool::VARIANTS
     )]
     InvalidMagicSchool(String),
+    #[error("Material details were given but the spell has no material component")]
+    MaterialDetailsWithoutMaterialComponent,
+    #[error("Invalid material cost \"{0}\" expected a positive amount of gold pieces")]
+    InvalidMaterialCost(i32),
+    #[error(
+        "Invalid caster class \"{0}\" expected one of: {:?}",
+        CasterClass::VARIANTS
+    )]
+    InvalidCasterClass(String),
+    #[error("Invalid dice \"{0}\" expected something like \"8d6\" or \"1d4+1\"")]
+    InvalidDice(String),
+    #[error("Invalid dice \"{0}\" expected at most 100 dice and a modifier of at most 100")]
+    TooManyDice(String),
+    #[error(
+        "Higher level dice \"{0}\" have to roll the same die as the damage or healing dice \"{1}\""
+    )]
+    MismatchedHigherLevelDice(String, String),
+    #[error("Higher level dice require a leveled spell with damage or healing dice")]
+    HigherLevelDiceWithoutLeveledEffect,
+    #[error("Cantrip scaling requires a cantrip with damage or healing dice")]
+    CantripScalingWithoutCantripEffect,
+    #[error(
+        "Invalid casting time unit \"{0}\" expected one of: {:?}",
+        CastingTimeUnit::VARIANTS
+    )]
+    InvalidCastingTimeUnit(String),
+    #[error("Invalid casting time quantity \"{0}\" expected a positive number")]
+    InvalidCastingTimeQuantity(i32),
+    #[error("Only casting times in minutes or hours can have a quantity other than 1")]
+    CastingTimeQuantityWithoutDuration,
+    #[error("A reaction trigger was given but the spell isn't cast as a reaction")]
+    ReactionTriggerWithoutReaction,
+    #[error(
+        "Invalid range type \"{0}\" expected one of: {:?}",
+        RangeType::VARIANTS
+    )]
+    InvalidRangeType(String),
+    #[error(
+        "Invalid distance unit \"{0}\" expected one of: {:?}",
+        DistanceUnit::VARIANTS
+    )]
+    InvalidDistanceUnit(String),
+    #[error("Invalid range distance \"{0}\" expected 1 to 100000")]
+    InvalidRangeDistance(i32),
+    #[error("A range distance is required for and only allowed with ranges of type \"Distance\"")]
+    RangeDistanceWithoutDistanceRange,
+    #[error(
+        "Invalid area shape \"{0}\" expected one of: {:?}",
+        AreaShape::VARIANTS
+    )]
+    InvalidAreaShape(String),
+    #[error("Invalid area size \"{0}\" expected a positive number of feet")]
+    InvalidAreaSize(i32),
+    #[error("An area of effect requires both a shape and a size")]
+    IncompleteArea,
+    #[error(
+        "Invalid duration unit \"{0}\" expected one of: {:?}",
+        DurationUnit::VARIANTS
+    )]
+    InvalidDurationUnit(String),
+    #[error("Invalid duration quantity \"{0}\" expected 1 to 10000")]
+    InvalidDurationQuantity(i32),
+    #[error("A duration quantity is required for and only allowed with durations in rounds, minutes, hours or days")]
+    DurationQuantityWithoutTimedDuration,
+    #[error("Only durations in rounds, minutes, hours or days can be \"up to\" a duration")]
+    UpToWithoutTimedDuration,
+    #[error("A concentration spell can't have an instantaneous duration")]
+    ConcentrationWithInstantaneousDuration,
+    #[error(
+        "Invalid damage type \"{0}\" expected one of: {:?}",
+        DamageType::VARIANTS
+    )]
+    InvalidDamageType(String),
+    #[error(
+        "Invalid save ability \"{0}\" expected one of: {:?}",
+        Ability::VARIANTS
+    )]
+    InvalidSaveAbility(String),
+    #[error(
+        "Invalid attack type \"{0}\" expected one of: {:?}",
+        AttackType::VARIANTS
+    )]
+    InvalidAttackType(String),
+    #[error("Invalid condition \"{0}\" expected one of: {:?}", Condition::VARIANTS)]
+    InvalidCondition(String),
+    #[error("Invalid tag \"{0}\" expected 1 to 50 characters")]
+    InvalidTag(String),
+    #[error("Invalid page \"{0}\" expected a positive number")]
+    InvalidPage(i32),
+    #[error("A page requires a source")]
+    PageWithoutSource,
+    #[error("Invalid license \"{0}\" expected one of: {:?}", License::VARIANTS)]
+    InvalidLicense(String),
+    #[error("A license label is required for and only allowed with a \"Custom\" license")]
+    LicenseLabelWithoutCustomLicense,
+    #[error("The license of a published spell has to allow redistribution")]
+    PublishedLicenseForbidsRedistribution,
+}
+
+impl SpellValidationError {
+    /// Maps a violated check constraint of the spells table to the validation error it enforces.
+    pub fn from_database_error(e: &diesel::result::Error) -> Option<Self> {
+        let diesel::result::Error::DatabaseError(DatabaseErrorKind::CheckViolation, info) = e
+        else {
+            return None;
+        };
+        match info.constraint_name()? {
+            "spells_material_details" => Some(Self::MaterialDetailsWithoutMaterialComponent),
+            "spells_higher_level_dice_leveled" => Some(Self::HigherLevelDiceWithoutLeveledEffect),
+            "spells_cantrip_scaling_cantrip" => Some(Self::CantripScalingWithoutCantripEffect),
+            "spells_casting_time_quantity" => Some(Self::CastingTimeQuantityWithoutDuration),
+            "spells_reaction_trigger_reaction" => Some(Self::ReactionTriggerWithoutReaction),
+            "spells_range_distance" => Some(Self::RangeDistanceWithoutDistanceRange),
+            "spells_area" => Some(Self::IncompleteArea),
+            "spells_duration_quantity" => Some(Self::DurationQuantityWithoutTimedDuration),
+            "spells_duration_up_to" => Some(Self::UpToWithoutTimedDuration),
+            "spells_concentration_duration" => Some(Self::ConcentrationWithInstantaneousDuration),
+            "spells_page" => Some(Self::PageWithoutSource),
+            "spells_license_label" => Some(Self::LicenseLabelWithoutCustomLicense),
+            "spells_published_license" => Some(Self::PublishedLicenseForbidsRedistribution),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum HomebrewSchoolValidationError {
+    #[error("Invalid school name \"{0}\" expected 1 to 50 characters")]
+    InvalidName(String),
+    #[error("\"{0}\" is a standard school of magic and can't be a homebrew school")]
+    StandardSchoolName(String),
+}
+
+#[derive(Debug, Error)]
+pub enum SpellEffectError {
+    #[error("Your spell \"{0}\" has no damage or healing dice")]
+    NoEffectDice(String),
+    #[error("Invalid dice \"{0}\" expected something like \"8d6\" or \"1d4+1\"")]
+    InvalidDice(String),
+    #[error(
+        "Higher level dice \"{0}\" have to roll the same die as the damage or healing dice \"{1}\""
+    )]
+    MismatchedHigherLevelDice(String, String),
+    #[error("Invalid slot level \"{0}\" expected {1} to 9")]
+    InvalidSlotLevel(i32, i32),
+    #[error("Invalid character level \"{0}\" expected 1 to 20")]
+    InvalidCharacterLevel(i32),
+    #[error("Your spell \"{0}\" rolls too many dice to compute its effect")]
+    TooManyDice(String),
+}
+
+#[derive(Debug, Error)]
+pub enum ImageValidationError {
+    #[error("No image was uploaded, expected a file in the \"image\" field")]
+    MissingImage,
+    #[error("The image is larger than {0} MiB")]
+    TooLarge(usize),
+    #[error("Unsupported image, expected a PNG, JPEG, GIF or WebP image")]
+    UnsupportedFormat,
+    #[error("The image is {0}x{1} pixels, expected at most {2}x{2}")]
+    TooManyPixels(u32, u32, u32),
+    #[error("The image is damaged and could not be read")]
+    Unreadable,
+}
+
+#[derive(Debug, Error)]
+pub enum SortValidationError {
+    #[error("Invalid sort key \"{0}\" expected one of: {:?}", SortBy::VARIANTS)]
+    InvalidKey(String),
+    #[error("Only public spells can be sorted by \"{0}\"")]
+    PublicOnlyKey(SortBy),
+    #[error("Only searches with \"q\" or a fuzzy \"name\" can be sorted by relevance")]
+    RelevanceWithoutSearch,
+}
+
+#[derive(Debug, Error)]
+pub enum FilterValidationError {
+    #[error("A filter needs exactly one of \"and\", \"or\", \"not\" or a \"field\" with one operator")]
+    InvalidNode,
+    #[error("The field \"{0}\" can't be filtered with \"{1}\"")]
+    UnsupportedOperator(FilterField, &'static str),
+    #[error("Invalid value to filter the field \"{0}\" with")]
+    InvalidValue(FilterField),
+    #[error("Invalid duration_min_minutes \"{0}\" expected 0 to 14400000")]
+    InvalidDurationMinMinutes(i32),
+}
+
+#[derive(Debug, Error)]
+pub enum PageValidationError {
+    #[error("Invalid limit \"{0}\" expected a positive number")]
+    InvalidLimit(i64),
+    #[error("Invalid cursor, expected the next_cursor of a previous page of the same query")]
+    InvalidCursor,
 }
 
 #[derive(Debug, Error)]
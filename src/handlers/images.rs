@@ -0,0 +1,173 @@
+use axum::{
+    extract::{multipart::MultipartError, Multipart, Path},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+
+use crate::{
+    errors::ImageValidationError,
+    establish_connection,
+    images::{decode_image, encode_image, read_image, store_image, MAX_IMAGE_BYTES},
+    repositories, IntoResource,
+};
+
+/// Stored images never change, their name is the hash of their content.
+const IMAGE_CACHE_CONTROL: &str = "public, max-age=31536000, immutable";
+
+pub async fn put_spell_image(
+    Extension(user_id): Extension<i32>,
+    Path(nanoid): Path<String>,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse, StatusCode> {
+    let conn = &mut establish_connection();
+
+    if let Err(e) = repositories::spells::get_spell_by_nanoid(conn, user_id, &nanoid) {
+        return match e {
+            diesel::result::Error::NotFound => Ok((
+                StatusCode::NOT_FOUND,
+                format!(
+                    "You don't have a spell with the id \"{}\" in your spellbook",
+                    nanoid
+                ),
+            )
+                .into_response()),
+            _ => {
+                let msg = "Failed to retrieve spell";
+                eprintln!("{}: {}", msg, e);
+                Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
+            }
+        };
+    }
+
+    let upload = loop {
+        match multipart.next_field().await {
+            Ok(Some(field)) if field.name() == Some("image") => match field.bytes().await {
+                Ok(upload) => break upload,
+                Err(e) => return Ok(multipart_error(e)),
+            },
+            Ok(Some(_)) => continue,
+            Ok(None) => {
+                return Ok((
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    ImageValidationError::MissingImage.to_string(),
+                )
+                    .into_response())
+            }
+            Err(e) => return Ok(multipart_error(e)),
+        }
+    };
+
+    // decoding and encoding an image takes a while, which would hold up other requests
+    let image = tokio::task::spawn_blocking(move || {
+        decode_image(&upload).map(|(image, format)| encode_image(image, format))
+    })
+    .await;
+    let image = match image {
+        Ok(Ok(Ok(image))) => image,
+        Ok(Err(e)) => return Ok((StatusCode::UNPROCESSABLE_ENTITY, e.to_string()).into_response()),
+        Ok(Ok(Err(e))) => {
+            let msg = "Failed to encode image";
+            eprintln!("{}: {}", msg, e);
+            return Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response());
+        }
+        Err(e) => {
+            let msg = "Failed to process image";
+            eprintln!("{}: {}", msg, e);
+            return Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response());
+        }
+    };
+    let file_name = match store_image(&image) {
+        Ok(file_name) => file_name,
+        Err(e) => {
+            let msg = "Failed to store image";
+            eprintln!("{}: {}", msg, e);
+            return Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response());
+        }
+    };
+
+    match repositories::spells::set_spell_image(conn, user_id, &nanoid, Some(&file_name)) {
+        Ok(spell) => Ok(Json(spell.into_resource()).into_response()),
+        Err(e) => {
+            let msg = "Failed to update spell";
+            eprintln!("{}: {}", msg, e);
+            Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
+        }
+    }
+}
+
+fn multipart_error(e: MultipartError) -> Response {
+    match e.status() {
+        StatusCode::PAYLOAD_TOO_LARGE => (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            ImageValidationError::TooLarge(MAX_IMAGE_BYTES / 1024 / 1024).to_string(),
+        )
+            .into_response(),
+        status => (status, e.body_text()).into_response(),
+    }
+}
+
+pub async fn delete_spell_image(
+    Extension(user_id): Extension<i32>,
+    Path(nanoid): Path<String>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let conn = &mut establish_connection();
+
+    // the file itself may be shared with copies of the spell, unused files are removed later on
+    match repositories::spells::set_spell_image(conn, user_id, &nanoid, None) {
+        Ok(spell) => Ok(Json(spell.into_resource()).into_response()),
+        Err(diesel::result::Error::NotFound) => Ok((
+            StatusCode::NOT_FOUND,
+            format!(
+                "You don't have a spell with the id \"{}\" in your spellbook",
+                nanoid
+            ),
+        )
+            .into_response()),
+        Err(e) => {
+            let msg = "Failed to update spell";
+            eprintln!("{}: {}", msg, e);
+            Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
+        }
+    }
+}
+
+pub async fn get_image(
+    Path(file_name): Path<String>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, StatusCode> {
+    let etag = format!("\"{}\"", file_name);
+    let cache_headers = [
+        (header::CACHE_CONTROL, IMAGE_CACHE_CONTROL.to_string()),
+        (header::ETAG, etag.to_string()),
+    ];
+
+    if headers
+        .get(header::IF_NONE_MATCH)
+        .is_some_and(|if_none_match| if_none_match.as_bytes() == etag.as_bytes())
+    {
+        return Ok((StatusCode::NOT_MODIFIED, cache_headers).into_response());
+    }
+
+    match read_image(&file_name) {
+        Ok(Some((bytes, content_type))) => Ok((
+            cache_headers,
+            [
+                (header::CONTENT_TYPE, content_type),
+                (header::X_CONTENT_TYPE_OPTIONS, "nosniff"),
+            ],
+            bytes,
+        )
+            .into_response()),
+        Ok(None) => Ok((
+            StatusCode::NOT_FOUND,
+            format!("There is no image \"{}\"", file_name),
+        )
+            .into_response()),
+        Err(e) => {
+            let msg = "Failed to read image";
+            eprintln!("{}: {}", msg, e);
+            Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
+        }
+    }
+}
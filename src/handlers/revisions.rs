@@ -0,0 +1,247 @@
+use axum::{
+    extract::{Path, Query},
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
+    Extension, Json,
+};
+use diesel::result::DatabaseErrorKind;
+
+use crate::{
+    errors::SpellValidationError,
+    establish_connection,
+    handlers::spells::missing_or_outdated,
+    if_match, repositories,
+    requests::revisions::RevisionDiffRequest,
+    resources::revisions::{SpellRevisionResource, SpellSnapshotResource},
+    IntoCollection, IntoResource,
+};
+
+pub async fn get_spell_revisions(
+    Extension(user_id): Extension<i32>,
+    Path(nanoid): Path<String>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let conn = &mut establish_connection();
+
+    let revisions = repositories::spells::get_spell_by_nanoid(conn, user_id, &nanoid)
+        .and_then(|spell| repositories::revisions::get_revisions(conn, &spell));
+
+    match revisions {
+        Ok(revisions) => {
+            let revisions: Vec<SpellRevisionResource> = revisions.into_collection();
+            Ok(Json(revisions).into_response())
+        }
+        Err(diesel::result::Error::NotFound) => Ok((
+            StatusCode::NOT_FOUND,
+            format!(
+                "You don't have a spell with the id \"{}\" in your spellbook",
+                nanoid
+            ),
+        )
+            .into_response()),
+        Err(e) => {
+            let msg = "Failed to retrieve revisions";
+            eprintln!("{}: {}", msg, e);
+            Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
+        }
+    }
+}
+
+pub async fn get_spell_revision(
+    Extension(user_id): Extension<i32>,
+    Path((nanoid, revision)): Path<(String, i32)>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let conn = &mut establish_connection();
+
+    let spell = match repositories::spells::get_spell_by_nanoid(conn, user_id, &nanoid) {
+        Ok(spell) => spell,
+        Err(diesel::result::Error::NotFound) => {
+            return Ok((
+                StatusCode::NOT_FOUND,
+                format!(
+                    "You don't have a spell with the id \"{}\" in your spellbook",
+                    nanoid
+                ),
+            )
+                .into_response())
+        }
+        Err(e) => {
+            let msg = "Failed to retrieve spell";
+            eprintln!("{}: {}", msg, e);
+            return Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response());
+        }
+    };
+
+    match repositories::revisions::get_revision(conn, &spell, revision) {
+        Ok(revision) => {
+            let revision: SpellSnapshotResource = revision.into_resource();
+            Ok(Json(revision).into_response())
+        }
+        Err(diesel::result::Error::NotFound) => Ok((
+            StatusCode::NOT_FOUND,
+            format!("Your spell \"{}\" has no revision {}", spell.name, revision),
+        )
+            .into_response()),
+        Err(e) => {
+            let msg = "Failed to retrieve revision";
+            eprintln!("{}: {}", msg, e);
+            Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
+        }
+    }
+}
+
+pub async fn get_spell_revision_diff(
+    Extension(user_id): Extension<i32>,
+    Path(nanoid): Path<String>,
+    Query(request): Query<RevisionDiffRequest>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let conn = &mut establish_connection();
+
+    let spell = match repositories::spells::get_spell_by_nanoid(conn, user_id, &nanoid) {
+        Ok(spell) => spell,
+        Err(diesel::result::Error::NotFound) => {
+            return Ok((
+                StatusCode::NOT_FOUND,
+                format!(
+                    "You don't have a spell with the id \"{}\" in your spellbook",
+                    nanoid
+                ),
+            )
+                .into_response())
+        }
+        Err(e) => {
+            let msg = "Failed to retrieve spell";
+            eprintln!("{}: {}", msg, e);
+            return Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response());
+        }
+    };
+
+    let mut snapshots = Vec::new();
+    for revision in [request.from, request.to] {
+        match repositories::revisions::get_revision(conn, &spell, revision) {
+            Ok((revision, _)) => snapshots.push(revision.snapshot()),
+            Err(diesel::result::Error::NotFound) => {
+                return Ok((
+                    StatusCode::NOT_FOUND,
+                    format!("Your spell \"{}\" has no revision {}", spell.name, revision),
+                )
+                    .into_response())
+            }
+            Err(e) => {
+                let msg = "Failed to retrieve revision";
+                eprintln!("{}: {}", msg, e);
+                return Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response());
+            }
+        }
+    }
+
+    let diff = match (&snapshots[0], &snapshots[1]) {
+        (Ok(from), Ok(to)) => from.diff(to),
+        (Err(e), _) | (_, Err(e)) => {
+            let msg = "Failed to read revision";
+            eprintln!("{}: {}", msg, e);
+            return Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response());
+        }
+    };
+
+    match diff {
+        Ok(changes) => Ok(Json(changes.into_collection()).into_response()),
+        Err(e) => {
+            let msg = "Failed to compare revisions";
+            eprintln!("{}: {}", msg, e);
+            Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
+        }
+    }
+}
+
+pub async fn revert_spell(
+    Extension(user_id): Extension<i32>,
+    Path((nanoid, revision)): Path<(String, i32)>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, StatusCode> {
+    let conn = &mut establish_connection();
+    let versions = if_match(&headers);
+
+    let spell = match repositories::spells::get_spell_by_nanoid(conn, user_id, &nanoid) {
+        Ok(spell) => spell,
+        Err(diesel::result::Error::NotFound) => {
+            return Ok((
+                StatusCode::NOT_FOUND,
+                format!(
+                    "You don't have a spell with the id \"{}\" in your spellbook",
+                    nanoid
+                ),
+            )
+                .into_response())
+        }
+        Err(e) => {
+            let msg = "Failed to retrieve spell";
+            eprintln!("{}: {}", msg, e);
+            return Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response());
+        }
+    };
+
+    let snapshot = match repositories::revisions::get_revision(conn, &spell, revision) {
+        Ok((revision, _)) => match revision.snapshot() {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                let msg = "Failed to read revision";
+                eprintln!("{}: {}", msg, e);
+                return Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response());
+            }
+        },
+        Err(diesel::result::Error::NotFound) => {
+            return Ok((
+                StatusCode::NOT_FOUND,
+                format!("Your spell \"{}\" has no revision {}", spell.name, revision),
+            )
+                .into_response())
+        }
+        Err(e) => {
+            let msg = "Failed to retrieve revision";
+            eprintln!("{}: {}", msg, e);
+            return Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response());
+        }
+    };
+
+    // another spell may have taken the old name in the meantime
+    if let Ok(other_spell) = repositories::spells::get_spell_by_name(conn, user_id, &snapshot.name)
+    {
+        if other_spell.id != spell.id {
+            return Ok((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!(
+                    "You already have a spell with the name \"{}\" in your spellbook.",
+                    other_spell.name
+                ),
+            )
+                .into_response());
+        }
+    }
+
+    match repositories::spells::revert_spell(conn, &spell, &snapshot, versions.as_deref()) {
+        Ok(spell) => Ok((
+            [(header::ETAG, spell.0.etag())],
+            Json(spell.into_resource()),
+        )
+            .into_response()),
+        Err(diesel::result::Error::NotFound) => Ok(missing_or_outdated(conn, user_id, &nanoid)),
+        Err(diesel::result::Error::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _)) => {
+            Ok((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!(
+                    "The homebrew school \"{}\" of revision {} no longer exists",
+                    snapshot.magic_school, revision
+                ),
+            )
+                .into_response())
+        }
+        Err(e) => {
+            if let Some(e) = SpellValidationError::from_database_error(&e) {
+                return Ok((StatusCode::UNPROCESSABLE_ENTITY, e.to_string()).into_response());
+            }
+            let msg = "Failed to revert spell";
+            eprintln!("{}: {}", msg, e);
+            Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
+        }
+    }
+}
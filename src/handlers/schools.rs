@@ -0,0 +1,136 @@
+use axum::{extract::Path, http::StatusCode, response::IntoResponse, Extension, Json};
+use diesel::result::DatabaseErrorKind;
+
+use crate::{
+    establish_connection,
+    models::schools::{NewHomebrewSchool, UpdatedHomebrewSchool},
+    repositories,
+    requests::schools::{CreateHomebrewSchoolRequest, UpdateHomebrewSchoolRequest},
+    IntoCollection, IntoResource, Validate,
+};
+
+pub async fn get_schools(
+    Extension(user_id): Extension<i32>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let conn = &mut establish_connection();
+    match repositories::schools::get_schools(conn, user_id) {
+        Ok(schools) => Ok(Json(schools.into_collection()).into_response()),
+        Err(e) => {
+            let msg = "Failed to retrieve homebrew schools";
+            eprintln!("{}: {}", msg, e);
+            Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
+        }
+    }
+}
+
+pub async fn post_school(
+    Extension(user_id): Extension<i32>,
+    Json(request): Json<CreateHomebrewSchoolRequest>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let conn = &mut establish_connection();
+
+    if let Err(e) = request.validate() {
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, e.to_string()).into_response());
+    }
+
+    let new_school = NewHomebrewSchool {
+        user_id,
+        name: request.name.trim(),
+        description: request.description.as_deref(),
+        shared: request.shared,
+    };
+
+    match repositories::schools::insert_school(conn, new_school) {
+        Ok(school) => Ok(Json(school.into_resource()).into_response()),
+        Err(diesel::result::Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => Ok((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!(
+                "You already have a homebrew school with the name \"{}\"",
+                request.name.trim()
+            ),
+        )
+            .into_response()),
+        Err(e) => {
+            let msg = "Failed to insert homebrew school";
+            eprintln!("{}: {}", msg, e);
+            Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
+        }
+    }
+}
+
+pub async fn update_school(
+    Extension(user_id): Extension<i32>,
+    Path(name): Path<String>,
+    Json(request): Json<UpdateHomebrewSchoolRequest>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let conn = &mut establish_connection();
+
+    if let Err(e) = request.validate() {
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, e.to_string()).into_response());
+    }
+
+    let updated_school = UpdatedHomebrewSchool::from_request(&request);
+
+    match repositories::schools::update_school(conn, user_id, &name, updated_school) {
+        Ok(school) => Ok(Json(school.into_resource()).into_response()),
+        Err(diesel::result::Error::NotFound) => Ok((
+            StatusCode::NOT_FOUND,
+            format!(
+                "You don't have a homebrew school with the name \"{}\"",
+                name
+            ),
+        )
+            .into_response()),
+        Err(diesel::result::Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => Ok((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!(
+                "You already have a homebrew school with the name \"{}\"",
+                request.name.as_deref().unwrap_or_default().trim()
+            ),
+        )
+            .into_response()),
+        Err(e) => {
+            let msg = "Failed to update homebrew school";
+            eprintln!("{}: {}", msg, e);
+            Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
+        }
+    }
+}
+
+pub async fn delete_school(
+    Extension(user_id): Extension<i32>,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let conn = &mut establish_connection();
+
+    match repositories::schools::delete_school(conn, user_id, &name) {
+        Ok(1) => Ok((
+            StatusCode::OK,
+            "The homebrew school was successfully deleted",
+        )
+            .into_response()),
+        Ok(_) => Ok((
+            StatusCode::NOT_FOUND,
+            format!(
+                "You don't have a homebrew school with the name \"{}\"",
+                name
+            ),
+        )
+            .into_response()),
+        Err(diesel::result::Error::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _)) => {
+            Ok((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!(
+                    "Your homebrew school \"{}\" is still used by some of your spells",
+                    name
+                ),
+            )
+                .into_response())
+        }
+        Err(e) => {
+            let msg = "Failed to delete homebrew school";
+            eprintln!("{}: {}", msg, e);
+            Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
+        }
+    }
+}
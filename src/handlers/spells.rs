@@ -1,22 +1,70 @@
-use axum::{extract::Path, http::StatusCode, response::IntoResponse, Extension, Json};
+use std::str::FromStr;
+
+use axum::{
+    extract::{Path, Query},
+    http::{header, HeaderMap, StatusCode, Uri},
+    response::{AppendHeaders, IntoResponse, Response},
+    Extension, Json,
+};
+use diesel::{Connection, PgConnection};
 use nanoid::nanoid;
+use serde::Serialize;
 
 use crate::{
-    establish_connection,
-    models::spells::{NewSpell, UpdatedSpell},
+    enums::License,
+    errors::{PageValidationError, SpellValidationError},
+    establish_connection, if_match,
+    markdown::render_description,
+    models::{
+        revisions::SpellSnapshot,
+        schools::HomebrewSchool,
+        spells::{NewSpell, SpellDetails, UpdatedSpell},
+    },
     repositories,
-    requests::spells::{
-        CreateSpellRequest, QueryPublicSpellsRequest, QuerySpellsRequest, UpdateSpellRequest,
+    requests::{
+        pages::PageRequest,
+        spells::{
+            CreateSpellRequest, DescriptionPreviewRequest, GetSpellsRequest, PatchSpellRequest,
+            QueryPublicSpellsRequest, QuerySpellsRequest, SortKey, SpellEffectRequest, SpellSchool,
+            SpellSuggestionRequest, SpellTagsRequest, TagAutocompleteRequest, UpdateSpellRequest,
+        },
+    },
+    resources::{
+        pages::PageResource,
+        spells::{
+            DescriptionPreviewResource, PublicSpellResource, SpellMatchResource,
+            SpellNotFoundResource, SpellResource, SpellSuggestionResource,
+        },
     },
     IntoCollection, IntoResource, Validate,
 };
 
+/// Clients accepting this media type get listings as a page together with the cursor to the next
+/// page and the total, instead of the bare list of spells.
+const PAGE_MEDIA_TYPE: &str = "application/vnd.spellbook.v2+json";
+const SUGGESTION_LIMIT: i64 = 10;
+
 pub async fn get_spells(
     Extension(user_id): Extension<i32>,
+    uri: Uri,
+    headers: HeaderMap,
+    Query(page): Query<PageRequest>,
+    Query(request): Query<GetSpellsRequest>,
 ) -> Result<impl IntoResponse, StatusCode> {
+    let sort = match request.sort_keys() {
+        Ok(sort) => sort,
+        Err(e) => return Ok((StatusCode::UNPROCESSABLE_ENTITY, e.to_string()).into_response()),
+    };
+    if let Err(e) = validate_page(&page, &sort) {
+        return Ok(e.into_response());
+    }
+
     let conn = &mut establish_connection();
-    match repositories::spells::get_spells(conn, user_id) {
-        Ok(spells) => Ok(Json(spells.into_collection()).into_response()),
+    match repositories::spells::get_spells(conn, user_id, &sort, &page) {
+        Ok(spells) => {
+            let spells: PageResource<SpellResource> = spells.into_resource();
+            Ok(page_response(&headers, spells, Some(&uri)))
+        }
         Err(e) => {
             let msg = "Failed to retrieve spells";
             eprintln!("{}: {}", msg, e);
@@ -31,17 +79,46 @@ pub async fn get_spell(
 ) -> Result<impl IntoResponse, StatusCode> {
     let conn = &mut establish_connection();
 
-    match repositories::spells::get_spell_by_nanoid(conn, user_id, &nanoid) {
-        Ok(spell) => Ok(Json(spell.into_resource()).into_response()),
+    // spells can be looked up by their exact name as well
+    let spell = repositories::spells::get_spell_by_nanoid(conn, user_id, &nanoid)
+        .or_else(|e| match e {
+            diesel::result::Error::NotFound => {
+                repositories::spells::get_spell_by_name(conn, user_id, &nanoid)
+            }
+            e => Err(e),
+        })
+        .and_then(|spell| repositories::spells::get_spell_details(conn, spell));
+
+    match spell {
+        Ok(spell) => Ok((
+            [(header::ETAG, spell.0.etag())],
+            Json(spell.into_resource()),
+        )
+            .into_response()),
         Err(e) => match e {
-            diesel::result::Error::NotFound => Ok((
-                StatusCode::NOT_FOUND,
-                format!(
-                    "You don't have a spell with the id \"{}\" in your spellbook",
-                    nanoid
-                ),
-            )
-                .into_response()),
+            diesel::result::Error::NotFound => {
+                // the name may be mistyped or misremembered, which makes for suggestions
+                let did_you_mean =
+                    match repositories::spells::suggest_spells(conn, user_id, &nanoid, 5) {
+                        Ok(suggestions) => suggestions,
+                        Err(e) => {
+                            let msg = "Failed to suggest spells";
+                            eprintln!("{}: {}", msg, e);
+                            Vec::new()
+                        }
+                    };
+                Ok((
+                    StatusCode::NOT_FOUND,
+                    Json(SpellNotFoundResource {
+                        message: format!(
+                            "You don't have a spell with the id or name \"{}\" in your spellbook",
+                            nanoid
+                        ),
+                        did_you_mean: did_you_mean.into_collection(),
+                    }),
+                )
+                    .into_response())
+            }
             _ => {
                 let msg = "Failed to retrieve spell";
                 eprintln!("{}: {}", msg, e);
@@ -51,6 +128,43 @@ pub async fn get_spell(
     }
 }
 
+pub async fn get_spell_effect(
+    Extension(user_id): Extension<i32>,
+    Path(nanoid): Path<String>,
+    Query(request): Query<SpellEffectRequest>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let conn = &mut establish_connection();
+
+    match repositories::spells::get_spell_by_nanoid(conn, user_id, &nanoid) {
+        Ok(spell) => match spell.effect(request.slot_level, request.character_level) {
+            Ok(effect) => Ok(Json(effect.into_resource()).into_response()),
+            Err(e) => Ok((StatusCode::UNPROCESSABLE_ENTITY, e.to_string()).into_response()),
+        },
+        Err(diesel::result::Error::NotFound) => Ok((
+            StatusCode::NOT_FOUND,
+            format!(
+                "You don't have a spell with the id \"{}\" in your spellbook",
+                nanoid
+            ),
+        )
+            .into_response()),
+        Err(e) => {
+            let msg = "Failed to retrieve spell";
+            eprintln!("{}: {}", msg, e);
+            Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
+        }
+    }
+}
+
+pub async fn preview_description(
+    Json(request): Json<DescriptionPreviewRequest>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let preview = DescriptionPreviewResource {
+        description_html: render_description(&request.description),
+    };
+    Ok(Json(preview).into_response())
+}
+
 pub async fn post_spell(
     Extension(user_id): Extension<i32>,
     Json(request): Json<CreateSpellRequest>,
@@ -73,23 +187,64 @@ pub async fn post_spell(
             .into_response());
     }
 
+    let homebrew_school = match find_homebrew_school(conn, user_id, Some(&request.magic_school)) {
+        Ok(homebrew_school) => homebrew_school,
+        Err(response) => return Ok(response.into_response()),
+    };
+
     let new_spell = NewSpell {
         name: &request.name,
-        level: &request.level,
-        casting_time: &request.casting_time,
-        magic_school: &request.magic_school,
+        level: request.level,
+        casting_time_unit: &request.casting_time_unit,
+        casting_time_quantity: request.casting_time_quantity.unwrap_or(1),
+        reaction_trigger: request.reaction_trigger.as_deref(),
+        magic_school: request.magic_school.standard(),
         concentration: request.concentration,
-        range: &request.range,
-        duration: &request.duration,
+        range_type: &request.range_type,
+        range_distance: request.range_distance,
+        range_unit: request.range_unit(),
+        area_shape: request.area_shape.as_deref(),
+        area_size: request.area_size,
+        duration_unit: &request.duration_unit,
+        duration_quantity: request.duration_quantity,
+        duration_up_to: request.duration_up_to,
         description: &request.description,
         user_id,
         published: false,
         nanoid: &nanoid!(),
+        verbal: request.verbal,
+        somatic: request.somatic,
+        material: request.material,
+        material_description: request.material_description.as_deref(),
+        material_cost: request.material_cost,
+        material_consumed: request.material_consumed,
+        ritual: request.ritual,
+        damage_dice: request.damage_dice.as_deref(),
+        higher_levels: request.higher_levels.as_deref(),
+        higher_level_dice: request.higher_level_dice.as_deref(),
+        cantrip_scaling: request.cantrip_scaling,
+        healing_dice: request.healing_dice.as_deref(),
+        damage_types: &request.damage_types,
+        save_ability: request.save_ability.as_deref(),
+        attack_type: request.attack_type.as_deref(),
+        conditions: &request.conditions,
+        source: request.source.as_deref(),
+        page: request.page,
+        is_homebrew: request.is_homebrew,
+        license: request.license.as_deref(),
+        license_label: request.license_label.as_deref(),
+        homebrew_school_id: homebrew_school.map(|school| school.id),
+        description_html: render_description(&request.description),
+        image: None,
+        copied_from_id: None,
     };
 
-    match repositories::spells::insert_spell(conn, new_spell) {
+    match repositories::spells::insert_spell(conn, new_spell, &request.classes) {
         Ok(spell) => Ok(Json(spell.into_resource()).into_response()),
         Err(e) => {
+            if let Some(e) = SpellValidationError::from_database_error(&e) {
+                return Ok((StatusCode::UNPROCESSABLE_ENTITY, e.to_string()).into_response());
+            }
             let msg = "Failed to insert spell";
             eprintln!("{}: {}", msg, e);
             Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
@@ -100,43 +255,283 @@ pub async fn post_spell(
 pub async fn update_spell(
     Extension(user_id): Extension<i32>,
     Path(nanoid): Path<String>,
+    headers: HeaderMap,
     Json(request): Json<UpdateSpellRequest>,
 ) -> Result<impl IntoResponse, StatusCode> {
     let conn = &mut establish_connection();
+    Ok(replace_spell(
+        conn,
+        user_id,
+        &nanoid,
+        &request,
+        if_match(&headers).as_deref(),
+    ))
+}
+
+pub async fn patch_spell(
+    Extension(user_id): Extension<i32>,
+    Path(nanoid): Path<String>,
+    headers: HeaderMap,
+    Json(patch): Json<PatchSpellRequest>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let conn = &mut establish_connection();
 
+    let spell = match repositories::spells::get_spell_by_nanoid(conn, user_id, &nanoid)
+        .and_then(|spell| repositories::spells::get_spell_details(conn, spell))
+    {
+        Ok(spell) => spell,
+        Err(diesel::result::Error::NotFound) => {
+            return Ok((
+                StatusCode::NOT_FOUND,
+                format!(
+                    "You don't have a spell with the id \"{}\" in your spellbook",
+                    nanoid
+                ),
+            )
+                .into_response())
+        }
+        Err(e) => {
+            let msg = "Failed to retrieve spell";
+            eprintln!("{}: {}", msg, e);
+            return Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response());
+        }
+    };
+
+    // the patch is applied to this version, so it must not overwrite a later one
+    let versions = match if_match(&headers) {
+        Some(versions) if !versions.contains(&spell.0.version) => {
+            return Ok(precondition_failed(spell))
+        }
+        _ => vec![spell.0.version],
+    };
+
+    let request = match serde_json::to_value(SpellSnapshot::from_details(&spell))
+        .and_then(|current_spell| patch.apply(current_spell))
+    {
+        Ok(request) => request,
+        Err(e) if e.is_data() => {
+            return Ok((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("The patched spell is incomplete or invalid: {}", e),
+            )
+                .into_response())
+        }
+        Err(e) => {
+            let msg = "Failed to patch spell";
+            eprintln!("{}: {}", msg, e);
+            return Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response());
+        }
+    };
+
+    Ok(replace_spell(
+        conn,
+        user_id,
+        &nanoid,
+        &request,
+        Some(&versions),
+    ))
+}
+
+/// Validates a spell that replaces one in the spellbook as a whole, and stores it if the version
+/// of the spell is one of `versions`.
+fn replace_spell(
+    conn: &mut PgConnection,
+    user_id: i32,
+    nanoid: &str,
+    request: &UpdateSpellRequest,
+    versions: Option<&[i32]>,
+) -> Response {
     if let Err(e) = request.validate() {
-        return Ok((StatusCode::UNPROCESSABLE_ENTITY, e.to_string()).into_response());
+        return (StatusCode::UNPROCESSABLE_ENTITY, e.to_string()).into_response();
     }
 
-    if let Some(new_name) = &request.name {
-        if let Ok(spell) = repositories::spells::get_spell_by_name(conn, user_id, new_name) {
-            if spell.nanoid != nanoid {
-                return Ok((
-                    StatusCode::UNPROCESSABLE_ENTITY,
-                    format!(
-                        "You already have a spell with the name \"{}\" in your spellbook.",
-                        spell.name
-                    ),
-                )
-                    .into_response());
+    if let Ok(spell) = repositories::spells::get_spell_by_name(conn, user_id, &request.name) {
+        if spell.nanoid != nanoid {
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!(
+                    "You already have a spell with the name \"{}\" in your spellbook.",
+                    spell.name
+                ),
+            )
+                .into_response();
+        }
+    }
+
+    let homebrew_school = match find_homebrew_school(conn, user_id, Some(&request.magic_school)) {
+        Ok(homebrew_school) => homebrew_school,
+        Err(response) => return response.into_response(),
+    };
+
+    let updated_spell =
+        UpdatedSpell::from_request(request, homebrew_school.map(|school| school.id));
+
+    match repositories::spells::update_spell(
+        conn,
+        user_id,
+        nanoid,
+        updated_spell,
+        Some(&request.classes),
+        versions,
+    ) {
+        Ok(spell) => (
+            [(header::ETAG, spell.0.etag())],
+            Json(spell.into_resource()),
+        )
+            .into_response(),
+        Err(diesel::result::Error::NotFound) => missing_or_outdated(conn, user_id, nanoid),
+        Err(e) => {
+            if let Some(e) = SpellValidationError::from_database_error(&e) {
+                return (StatusCode::UNPROCESSABLE_ENTITY, e.to_string()).into_response();
             }
+            let msg = "Failed to update spell";
+            eprintln!("{}: {}", msg, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, msg).into_response()
         }
     }
+}
+
+/// Answers a change that found no spell to change: either there is none with that id, or its
+/// version isn't one `If-Match` allowed. Then the client gets the current spell to redo its
+/// change on.
+pub fn missing_or_outdated(conn: &mut PgConnection, user_id: i32, nanoid: &str) -> Response {
+    let spell = repositories::spells::get_spell_by_nanoid(conn, user_id, nanoid)
+        .and_then(|spell| repositories::spells::get_spell_details(conn, spell));
+
+    match spell {
+        Ok(spell) => precondition_failed(spell),
+        Err(diesel::result::Error::NotFound) => (
+            StatusCode::NOT_FOUND,
+            format!(
+                "You don't have a spell with the id \"{}\" in your spellbook",
+                nanoid
+            ),
+        )
+            .into_response(),
+        Err(e) => {
+            let msg = "Failed to retrieve spell";
+            eprintln!("{}: {}", msg, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, msg).into_response()
+        }
+    }
+}
+
+fn precondition_failed(spell: SpellDetails) -> Response {
+    (
+        StatusCode::PRECONDITION_FAILED,
+        [(header::ETAG, spell.0.etag())],
+        Json(spell.into_resource()),
+    )
+        .into_response()
+}
+
+/// Looks up the homebrew school a request names, standard schools don't need a lookup.
+fn find_homebrew_school(
+    conn: &mut PgConnection,
+    user_id: i32,
+    school: Option<&SpellSchool>,
+) -> Result<Option<HomebrewSchool>, (StatusCode, String)> {
+    let Some(SpellSchool::Homebrew(school_name)) = school else {
+        return Ok(None);
+    };
+    match repositories::schools::get_school_by_name(conn, user_id, school_name) {
+        Ok(homebrew_school) => Ok(Some(homebrew_school)),
+        Err(diesel::result::Error::NotFound) => Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            SpellValidationError::InvalidMagicSchool(school_name.to_string()).to_string(),
+        )),
+        Err(e) => {
+            let msg = "Failed to retrieve homebrew school";
+            eprintln!("{}: {}", msg, e);
+            Err((StatusCode::INTERNAL_SERVER_ERROR, msg.to_string()))
+        }
+    }
+}
 
-    let updated_spell = UpdatedSpell::from_request(&request);
+pub async fn add_spell_tags(
+    Extension(user_id): Extension<i32>,
+    Path(nanoid): Path<String>,
+    Json(request): Json<SpellTagsRequest>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let conn = &mut establish_connection();
+
+    if let Err(e) = request.validate() {
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, e.to_string()).into_response());
+    }
 
-    match repositories::spells::update_spell(conn, user_id, &nanoid, updated_spell) {
+    match repositories::spells::add_spell_tags(conn, user_id, &nanoid, &request.trimmed_tags()) {
         Ok(spell) => Ok(Json(spell.into_resource()).into_response()),
         Err(diesel::result::Error::NotFound) => Ok((
             StatusCode::NOT_FOUND,
             format!(
-                "You don't have a spell with the id \"{}\" in your spellbook.",
+                "You don't have a spell with the id \"{}\" in your spellbook",
                 nanoid
             ),
         )
             .into_response()),
         Err(e) => {
-            let msg = "Failed to update spell";
+            let msg = "Failed to tag spell";
+            eprintln!("{}: {}", msg, e);
+            Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
+        }
+    }
+}
+
+pub async fn remove_spell_tags(
+    Extension(user_id): Extension<i32>,
+    Path(nanoid): Path<String>,
+    Json(request): Json<SpellTagsRequest>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let conn = &mut establish_connection();
+
+    match repositories::spells::remove_spell_tags(conn, user_id, &nanoid, &request.trimmed_tags()) {
+        Ok(spell) => Ok(Json(spell.into_resource()).into_response()),
+        Err(diesel::result::Error::NotFound) => Ok((
+            StatusCode::NOT_FOUND,
+            format!(
+                "You don't have a spell with the id \"{}\" in your spellbook",
+                nanoid
+            ),
+        )
+            .into_response()),
+        Err(e) => {
+            let msg = "Failed to untag spell";
+            eprintln!("{}: {}", msg, e);
+            Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
+        }
+    }
+}
+
+pub async fn get_tags(
+    Extension(user_id): Extension<i32>,
+    Query(request): Query<TagAutocompleteRequest>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let conn = &mut establish_connection();
+
+    match repositories::spells::get_tag_counts(conn, user_id, request.prefix.as_deref()) {
+        Ok(tags) => Ok(Json(tags.into_collection()).into_response()),
+        Err(e) => {
+            let msg = "Failed to retrieve tags";
+            eprintln!("{}: {}", msg, e);
+            Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
+        }
+    }
+}
+
+pub async fn suggest_spells(
+    Extension(user_id): Extension<i32>,
+    Query(request): Query<SpellSuggestionRequest>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let conn = &mut establish_connection();
+
+    let prefix = request.prefix.trim();
+    if prefix.is_empty() {
+        return Ok(Json(Vec::<SpellSuggestionResource>::new()).into_response());
+    }
+    match repositories::spells::suggest_spells(conn, user_id, prefix, SUGGESTION_LIMIT) {
+        Ok(spells) => Ok(Json(spells.into_collection()).into_response()),
+        Err(e) => {
+            let msg = "Failed to suggest spells";
             eprintln!("{}: {}", msg, e);
             Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
         }
@@ -146,25 +541,100 @@ pub async fn update_spell(
 pub async fn delete_spell(
     Extension(user_id): Extension<i32>,
     Path(nanoid): Path<String>,
+    headers: HeaderMap,
 ) -> Result<impl IntoResponse, StatusCode> {
     let conn = &mut establish_connection();
 
-    match repositories::spells::delete_spell(conn, user_id, &nanoid) {
+    match repositories::spells::delete_spell(conn, user_id, &nanoid, if_match(&headers).as_deref())
+    {
         Ok(1) => Ok((
             StatusCode::OK,
-            "The spell was successfully erased from your spellbook",
+            "The spell was moved to the trash, you can restore it from there",
         )
             .into_response()),
-        Ok(_) => Ok((
-            StatusCode::NOT_FOUND,
+        Ok(_) => Ok(missing_or_outdated(conn, user_id, &nanoid)),
+        Err(e) => {
+            let msg = "Failed to erase spell";
+            eprintln!("{}: {}", msg, e);
+            Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
+        }
+    }
+}
+
+pub async fn get_trash(
+    Extension(user_id): Extension<i32>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let conn = &mut establish_connection();
+    match repositories::spells::get_trash(conn, user_id) {
+        Ok(spells) => Ok(Json(spells.into_collection()).into_response()),
+        Err(e) => {
+            let msg = "Failed to retrieve trash";
+            eprintln!("{}: {}", msg, e);
+            Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
+        }
+    }
+}
+
+pub async fn restore_spell(
+    Extension(user_id): Extension<i32>,
+    Path(nanoid): Path<String>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let conn = &mut establish_connection();
+
+    let spell = match repositories::spells::get_trashed_spell(conn, user_id, &nanoid) {
+        Ok(spell) => spell,
+        Err(diesel::result::Error::NotFound) => {
+            return Ok((
+                StatusCode::NOT_FOUND,
+                format!(
+                    "You don't have a spell with the id \"{}\" in your trash",
+                    nanoid
+                ),
+            )
+                .into_response())
+        }
+        Err(e) => {
+            let msg = "Failed to retrieve spell";
+            eprintln!("{}: {}", msg, e);
+            return Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response());
+        }
+    };
+
+    // a new spell may have taken the name while this one was in the trash
+    if let Ok(other_spell) = repositories::spells::get_spell_by_name(conn, user_id, &spell.name) {
+        return Ok((
+            StatusCode::UNPROCESSABLE_ENTITY,
             format!(
-                "You don't have a spell with the id \"{}\" in your spellbook.",
-                nanoid
+                "You already have a spell with the name \"{}\" in your spellbook.",
+                other_spell.name
             ),
+        )
+            .into_response());
+    }
+
+    match repositories::spells::restore_spell(conn, user_id, &nanoid) {
+        Ok(spell) => Ok(Json(spell.into_resource()).into_response()),
+        Err(e) => {
+            let msg = "Failed to restore spell";
+            eprintln!("{}: {}", msg, e);
+            Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
+        }
+    }
+}
+
+pub async fn empty_trash(
+    Extension(user_id): Extension<i32>,
+) -> Result<impl IntoResponse, StatusCode> {
+    let conn = &mut establish_connection();
+
+    match repositories::spells::empty_trash(conn, user_id) {
+        Ok(erased) => Ok((
+            StatusCode::OK,
+            format!("{} spell(s) were erased from your trash", erased),
         )
             .into_response()),
         Err(e) => {
-            let msg = "Failed to erase spell";
+            let msg = "Failed to empty trash";
             eprintln!("{}: {}", msg, e);
             Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
         }
@@ -174,26 +644,58 @@ pub async fn delete_spell(
 pub async fn publish_spell(
     Extension(user_id): Extension<i32>,
     Path(nanoid): Path<String>,
+    headers: HeaderMap,
 ) -> Result<impl IntoResponse, StatusCode> {
     let conn = &mut establish_connection();
+    let versions = if_match(&headers);
 
     match repositories::spells::get_spell_by_nanoid(conn, user_id, &nanoid) {
         Ok(spell) => {
+            if versions
+                .as_ref()
+                .is_some_and(|versions| !versions.contains(&spell.version))
+            {
+                return Ok(missing_or_outdated(conn, user_id, &nanoid));
+            }
             if spell.published {
                 return Ok((
                     StatusCode::UNPROCESSABLE_ENTITY,
                     format!("Your spell \"{}\" is already published", &spell.name),
-                ));
+                )
+                    .into_response());
+            }
+            if let Some(license) = &spell.license {
+                if License::from_str(license).is_ok_and(|l| !l.allows_redistribution()) {
+                    return Ok((
+                        StatusCode::UNPROCESSABLE_ENTITY,
+                        format!(
+                            "Your spell \"{}\" can't be published because its license \"{}\" forbids redistribution",
+                            &spell.name, license
+                        ),
+                    )
+                        .into_response());
+                }
             }
-            match repositories::spells::publish_spell(conn, user_id, &nanoid, true) {
-                Ok(_) => Ok((
+            match repositories::spells::publish_spell(
+                conn,
+                user_id,
+                &nanoid,
+                true,
+                versions.as_deref(),
+            ) {
+                Ok(spell) => Ok((
                     StatusCode::OK,
+                    [(header::ETAG, spell.etag())],
                     format!("Your spell \"{}\" was successfully published", &spell.name),
-                )),
+                )
+                    .into_response()),
+                Err(diesel::result::Error::NotFound) => {
+                    Ok(missing_or_outdated(conn, user_id, &nanoid))
+                }
                 Err(e) => {
-                    let msg = "Failed to publish spell".to_string();
+                    let msg = "Failed to publish spell";
                     eprintln!("{}: {}", msg, e);
-                    Ok((StatusCode::INTERNAL_SERVER_ERROR, msg))
+                    Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
                 }
             }
         }
@@ -203,11 +705,12 @@ pub async fn publish_spell(
                 "You don't have a spell with the id \"{}\" in your spellbook",
                 nanoid
             ),
-        )),
+        )
+            .into_response()),
         Err(e) => {
-            let msg = "Failed to retrieve spell".to_string();
+            let msg = "Failed to retrieve spell";
             eprintln!("{}: {}", msg, e);
-            Ok((StatusCode::INTERNAL_SERVER_ERROR, msg))
+            Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
         }
     }
 }
@@ -215,29 +718,49 @@ pub async fn publish_spell(
 pub async fn unpublish_spell(
     Extension(user_id): Extension<i32>,
     Path(nanoid): Path<String>,
+    headers: HeaderMap,
 ) -> Result<impl IntoResponse, StatusCode> {
     let conn = &mut establish_connection();
+    let versions = if_match(&headers);
 
     match repositories::spells::get_spell_by_nanoid(conn, user_id, &nanoid) {
         Ok(spell) => {
+            if versions
+                .as_ref()
+                .is_some_and(|versions| !versions.contains(&spell.version))
+            {
+                return Ok(missing_or_outdated(conn, user_id, &nanoid));
+            }
             if !spell.published {
                 return Ok((
                     StatusCode::UNPROCESSABLE_ENTITY,
                     format!("Your spell \"{}\" is not public", &spell.name),
-                ));
+                )
+                    .into_response());
             }
-            match repositories::spells::publish_spell(conn, user_id, &nanoid, false) {
-                Ok(_) => Ok((
+            match repositories::spells::publish_spell(
+                conn,
+                user_id,
+                &nanoid,
+                false,
+                versions.as_deref(),
+            ) {
+                Ok(spell) => Ok((
                     StatusCode::OK,
+                    [(header::ETAG, spell.etag())],
                     format!(
                         "Your spell \"{}\" was successfully unpublished",
                         &spell.name
                     ),
-                )),
+                )
+                    .into_response()),
+                Err(diesel::result::Error::NotFound) => {
+                    Ok(missing_or_outdated(conn, user_id, &nanoid))
+                }
                 Err(e) => {
-                    let msg = "Failed to unpublish spell".to_string();
+                    let msg = "Failed to unpublish spell";
                     eprintln!("{}: {}", msg, e);
-                    Ok((StatusCode::INTERNAL_SERVER_ERROR, msg))
+                    Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
                 }
             }
         }
@@ -247,23 +770,39 @@ pub async fn unpublish_spell(
                 "You don't have a spell with the id \"{}\" in your spellbook",
                 nanoid
             ),
-        )),
+        )
+            .into_response()),
         Err(e) => {
-            let msg = "Failed to retrieve spell".to_string();
+            let msg = "Failed to retrieve spell";
             eprintln!("{}: {}", msg, e);
-            Ok((StatusCode::INTERNAL_SERVER_ERROR, msg))
+            Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
         }
     }
 }
 
 pub async fn query_spells(
     Extension(user_id): Extension<i32>,
+    headers: HeaderMap,
+    Query(page): Query<PageRequest>,
     Json(request): Json<QuerySpellsRequest>,
 ) -> Result<impl IntoResponse, StatusCode> {
-    let conn = &mut establish_connection();
+    let sort = match request.sort_keys() {
+        Ok(sort) => sort,
+        Err(e) => return Ok((StatusCode::UNPROCESSABLE_ENTITY, e.to_string()).into_response()),
+    };
+    if let Err(e) = request.filters.validate() {
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, e.to_string()).into_response());
+    }
+    if let Err(e) = validate_page(&page, &sort) {
+        return Ok(e.into_response());
+    }
 
-    match repositories::spells::query_spells(conn, user_id, request) {
-        Ok(spells) => Ok(Json(spells.into_collection()).into_response()),
+    let conn = &mut establish_connection();
+    match repositories::spells::query_spells(conn, user_id, request, &sort, &page) {
+        Ok(spells) => {
+            let spells: PageResource<SpellMatchResource<SpellResource>> = spells.into_resource();
+            Ok(page_response(&headers, spells, None))
+        }
         Err(e) => {
             let msg = "Failed to query spells";
             eprintln!("{}: {}", msg, e);
@@ -274,12 +813,28 @@ pub async fn query_spells(
 
 pub async fn query_public_spells(
     Extension(user_id): Extension<i32>,
+    headers: HeaderMap,
+    Query(page): Query<PageRequest>,
     Json(request): Json<QueryPublicSpellsRequest>,
 ) -> Result<impl IntoResponse, StatusCode> {
-    let conn = &mut establish_connection();
+    let sort = match request.sort_keys() {
+        Ok(sort) => sort,
+        Err(e) => return Ok((StatusCode::UNPROCESSABLE_ENTITY, e.to_string()).into_response()),
+    };
+    if let Err(e) = request.filters.validate() {
+        return Ok((StatusCode::UNPROCESSABLE_ENTITY, e.to_string()).into_response());
+    }
+    if let Err(e) = validate_page(&page, &sort) {
+        return Ok(e.into_response());
+    }
 
-    match repositories::spells::query_public_spells(conn, user_id, request) {
-        Ok(spells_with_users) => Ok(Json(spells_with_users.into_collection()).into_response()),
+    let conn = &mut establish_connection();
+    match repositories::spells::query_public_spells(conn, user_id, request, &sort, &page) {
+        Ok(spells_with_users) => {
+            let spells: PageResource<SpellMatchResource<PublicSpellResource>> =
+                spells_with_users.into_resource();
+            Ok(page_response(&headers, spells, None))
+        }
         Err(e) => {
             let msg = "Failed to retrieve public spells";
             eprintln!("{}: {}", msg, e);
@@ -288,13 +843,81 @@ pub async fn query_public_spells(
     }
 }
 
+/// A cursor is only valid for a query with the same sort keys as the one it came from.
+fn validate_page(page: &PageRequest, sort: &[SortKey]) -> Result<(), (StatusCode, String)> {
+    if let Err(e) = page.validate() {
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, e.to_string()));
+    }
+    if page.cursor().is_some_and(|cursor| !cursor.fits(sort)) {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            PageValidationError::InvalidCursor.to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn accepts_pages(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|accept| accept.to_str().ok())
+        .any(|accept| accept.contains(PAGE_MEDIA_TYPE))
+}
+
+/// Links to the next page of a listing in the `Link` header, which works with both response
+/// shapes. The request is repeated with the cursor of the next page in place of its own. Queries
+/// sent in a body can't be repeated from a link, so they get no `uri` and only the next cursor
+/// of the page.
+fn page_response<T: Serialize>(
+    headers: &HeaderMap,
+    page: PageResource<T>,
+    uri: Option<&Uri>,
+) -> Response {
+    let next_link = uri
+        .zip(page.next_cursor.as_ref())
+        .map(|(uri, next_cursor)| {
+            let next_cursor = format!("cursor={}", next_cursor);
+            let mut parameters: Vec<&str> = uri
+                .query()
+                .unwrap_or_default()
+                .split('&')
+                .filter(|parameter| !parameter.is_empty() && !parameter.starts_with("cursor="))
+                .collect();
+            parameters.push(&next_cursor);
+            (
+                header::LINK,
+                format!("<{}?{}>; rel=\"next\"", uri.path(), parameters.join("&")),
+            )
+        });
+    let accepts_pages = accepts_pages(headers);
+
+    let headers = (
+        AppendHeaders(next_link),
+        [(header::VARY, header::ACCEPT.as_str())],
+    );
+    if accepts_pages {
+        (
+            headers,
+            [(header::CONTENT_TYPE, PAGE_MEDIA_TYPE)],
+            Json(page),
+        )
+            .into_response()
+    } else {
+        (headers, Json(page.items)).into_response()
+    }
+}
+
 pub async fn copy_public_spell(
     Extension(user_id): Extension<i32>,
     Path(nanoid): Path<String>,
 ) -> Result<impl IntoResponse, StatusCode> {
     let conn = &mut establish_connection();
 
-    let spell = repositories::spells::get_public_spell(conn, &nanoid);
+    let spell = repositories::spells::get_public_spell(conn, &nanoid).and_then(|spell| {
+        let classes = repositories::spells::get_spell_classes(conn, &spell)?;
+        Ok((spell, classes))
+    });
 
     match spell {
         Err(e) => match e {
@@ -309,7 +932,7 @@ pub async fn copy_public_spell(
                 Ok((StatusCode::INTERNAL_SERVER_ERROR, msg).into_response())
             }
         },
-        Ok(spell) => {
+        Ok((spell, classes)) => {
             if let Ok(spell) = repositories::spells::get_spell_by_name(conn, user_id, &spell.name) {
                 Ok((
                     StatusCode::UNPROCESSABLE_ENTITY,
@@ -320,21 +943,70 @@ pub async fn copy_public_spell(
                 )
                     .into_response())
             } else {
-                let copy = NewSpell {
-                    name: &spell.name,
-                    level: &spell.level,
-                    casting_time: &spell.casting_time,
-                    magic_school: &spell.magic_school,
-                    concentration: spell.concentration,
-                    range: &spell.range,
-                    duration: &spell.duration,
-                    description: &spell.description,
-                    user_id,
-                    published: false,
-                    nanoid: &nanoid!(),
-                };
-
-                match repositories::spells::insert_spell(conn, copy) {
+                let classes: Vec<String> = classes.into_iter().map(|c| c.caster_class).collect();
+
+                // a school copied for a spell that then fails to copy is rolled back with it
+                let copy = conn.transaction(|conn| {
+                    // the copy gets its own homebrew school, the original belongs to its author
+                    let homebrew_school = spell
+                        .homebrew_school_id
+                        .map(|school_id| {
+                            let school = repositories::schools::get_school(conn, school_id)?;
+                            repositories::schools::copy_school(conn, user_id, &school)
+                        })
+                        .transpose()?;
+
+                    let copy = NewSpell {
+                        name: &spell.name,
+                        level: spell.level,
+                        casting_time_unit: &spell.casting_time_unit,
+                        casting_time_quantity: spell.casting_time_quantity,
+                        reaction_trigger: spell.reaction_trigger.as_deref(),
+                        magic_school: spell.magic_school,
+                        concentration: spell.concentration,
+                        range_type: &spell.range_type,
+                        range_distance: spell.range_distance,
+                        range_unit: spell.range_unit.as_deref(),
+                        area_shape: spell.area_shape.as_deref(),
+                        area_size: spell.area_size,
+                        duration_unit: &spell.duration_unit,
+                        duration_quantity: spell.duration_quantity,
+                        duration_up_to: spell.duration_up_to,
+                        description: &spell.description,
+                        user_id,
+                        published: false,
+                        nanoid: &nanoid!(),
+                        verbal: spell.verbal,
+                        somatic: spell.somatic,
+                        material: spell.material,
+                        material_description: spell.material_description.as_deref(),
+                        material_cost: spell.material_cost,
+                        material_consumed: spell.material_consumed,
+                        ritual: spell.ritual,
+                        damage_dice: spell.damage_dice.as_deref(),
+                        higher_levels: spell.higher_levels.as_deref(),
+                        higher_level_dice: spell.higher_level_dice.as_deref(),
+                        cantrip_scaling: spell.cantrip_scaling,
+                        healing_dice: spell.healing_dice.as_deref(),
+                        damage_types: &spell.damage_types,
+                        save_ability: spell.save_ability.as_deref(),
+                        attack_type: spell.attack_type.as_deref(),
+                        conditions: &spell.conditions,
+                        source: spell.source.as_deref(),
+                        page: spell.page,
+                        is_homebrew: spell.is_homebrew,
+                        license: spell.license.as_deref(),
+                        license_label: spell.license_label.as_deref(),
+                        homebrew_school_id: homebrew_school.map(|school| school.id),
+                        description_html: render_description(&spell.description),
+                        // the copy shares the stored illustration instead of duplicating it
+                        image: spell.image.as_deref(),
+                        copied_from_id: Some(spell.id),
+                    };
+                    repositories::spells::insert_spell(conn, copy, &classes)
+                });
+
+                match copy {
                     Ok(spell) => Ok(Json(spell.into_resource()).into_response()),
                     Err(e) => {
                         let msg = "Failed to copy spell";
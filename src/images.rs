@@ -0,0 +1,185 @@
+use std::{
+    collections::HashSet,
+    env, fs,
+    io::{self, Cursor},
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+use dotenvy::dotenv;
+use image::{codecs::jpeg::JpegEncoder, DynamicImage, ImageFormat, ImageReader};
+use nanoid::nanoid;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+
+use crate::errors::ImageValidationError;
+
+/// Uploads larger than this are rejected before they are decoded.
+pub const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+
+/// The longest side an illustration may have, in pixels.
+const MAX_IMAGE_SIDE: u32 = 4096;
+
+const JPEG_QUALITY: u8 = 90;
+
+/// Unreferenced images younger than this may belong to an upload that isn't saved to its spell
+/// yet, so they are left alone.
+const UNUSED_IMAGE_GRACE: Duration = Duration::from_secs(60 * 60);
+
+/// Illustrations are stored in `IMAGE_DIR`, `images` if it isn't set.
+pub fn image_dir() -> PathBuf {
+    dotenv().ok();
+
+    env::var("IMAGE_DIR")
+        .unwrap_or_else(|_| "images".to_string())
+        .into()
+}
+
+pub fn image_url(file_name: &str) -> String {
+    format!("/images/{}", file_name)
+}
+
+/// Stored images are named by the SHA-256 hash of their content.
+fn is_image_name(file_name: &str) -> bool {
+    Regex::new(r"^[0-9a-f]{64}\.(png|jpg)$")
+        .unwrap()
+        .is_match(file_name)
+}
+
+/// An illustration that was re-encoded and is ready to be stored.
+pub struct Image {
+    pub bytes: Vec<u8>,
+    pub format: ImageFormat,
+}
+
+impl Image {
+    /// Identical images get the same name, so they are only stored once.
+    pub fn file_name(&self) -> String {
+        let extension = match self.format {
+            ImageFormat::Jpeg => "jpg",
+            _ => "png",
+        };
+        format!("{:x}.{}", Sha256::digest(&self.bytes), extension)
+    }
+}
+
+/// Checks an upload by its magic bytes, whatever its file name or content type claim, and
+/// decodes it once it is known to be small enough.
+pub fn decode_image(upload: &[u8]) -> Result<(DynamicImage, ImageFormat), ImageValidationError> {
+    if upload.len() > MAX_IMAGE_BYTES {
+        return Err(ImageValidationError::TooLarge(
+            MAX_IMAGE_BYTES / 1024 / 1024,
+        ));
+    }
+    let format = match image::guess_format(upload) {
+        Ok(
+            format @ (ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::Gif | ImageFormat::WebP),
+        ) => format,
+        _ => return Err(ImageValidationError::UnsupportedFormat),
+    };
+
+    // the dimensions are read from the header, so a huge image is never decoded
+    let (width, height) = ImageReader::with_format(Cursor::new(upload), format)
+        .into_dimensions()
+        .map_err(|_| ImageValidationError::Unreadable)?;
+    if width > MAX_IMAGE_SIDE || height > MAX_IMAGE_SIDE {
+        return Err(ImageValidationError::TooManyPixels(
+            width,
+            height,
+            MAX_IMAGE_SIDE,
+        ));
+    }
+
+    let image = ImageReader::with_format(Cursor::new(upload), format)
+        .decode()
+        .map_err(|_| ImageValidationError::Unreadable)?;
+    Ok((image, format))
+}
+
+/// Encodes a decoded image anew, which leaves out any metadata of the upload like the place a
+/// photo was taken. Photos stay JPEGs, everything else becomes a PNG.
+pub fn encode_image(image: DynamicImage, format: ImageFormat) -> Result<Image, image::ImageError> {
+    let mut bytes = Vec::new();
+    let format = match format {
+        ImageFormat::Jpeg => {
+            let encoder = JpegEncoder::new_with_quality(&mut bytes, JPEG_QUALITY);
+            DynamicImage::ImageRgb8(image.to_rgb8()).write_with_encoder(encoder)?;
+            ImageFormat::Jpeg
+        }
+        _ => {
+            image.write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)?;
+            ImageFormat::Png
+        }
+    };
+    Ok(Image { bytes, format })
+}
+
+/// Writes an image to the image directory unless an identical one is stored there already, and
+/// returns its file name.
+pub fn store_image(image: &Image) -> io::Result<String> {
+    let dir = image_dir();
+    fs::create_dir_all(&dir)?;
+
+    let file_name = image.file_name();
+    let path = dir.join(&file_name);
+    if path.exists() {
+        // an identical image no spell uses anymore may be old enough to be removed, it is made
+        // as young as a new upload so it survives until its spell refers to it
+        fs::File::options()
+            .append(true)
+            .open(&path)?
+            .set_modified(SystemTime::now())?;
+    } else {
+        // a half written image must never be served, so it only gets its name once complete
+        let partial_path = dir.join(format!("{}.partial", nanoid!()));
+        fs::write(&partial_path, &image.bytes)?;
+        fs::rename(&partial_path, &path)?;
+    }
+    Ok(file_name)
+}
+
+/// Reads a stored image together with its content type, `None` if there is no such image.
+pub fn read_image(file_name: &str) -> io::Result<Option<(Vec<u8>, &'static str)>> {
+    if !is_image_name(file_name) {
+        return Ok(None);
+    }
+    let content_type = match file_name.ends_with(".jpg") {
+        true => "image/jpeg",
+        false => "image/png",
+    };
+    match fs::read(image_dir().join(file_name)) {
+        Ok(bytes) => Ok(Some((bytes, content_type))),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Removes the stored images no spell refers to anymore, as well as leftovers of failed writes.
+pub fn remove_unused_images(used_images: &HashSet<String>) -> io::Result<usize> {
+    let dir = image_dir();
+    if !dir.exists() {
+        return Ok(0);
+    }
+
+    let mut removed = 0;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if used_images.contains(&file_name) {
+            continue;
+        }
+        let age = entry
+            .metadata()?
+            .modified()?
+            .elapsed()
+            .unwrap_or(Duration::ZERO);
+        if age < UNUSED_IMAGE_GRACE {
+            continue;
+        }
+        if is_image_name(&file_name) || file_name.ends_with(".partial") {
+            fs::remove_file(entry.path())?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
@@ -4,20 +4,24 @@ use std::{
     hash::{DefaultHasher, Hash, Hasher},
 };
 
-use axum::http::HeaderMap;
+use axum::http::{header, HeaderMap};
 use diesel::{Connection, PgConnection};
 use dotenvy::dotenv;
 use errors::AuthError;
 
+pub mod dice;
 pub mod enums;
 pub mod errors;
 pub mod handlers;
+pub mod images;
+pub mod markdown;
 pub mod middleware;
 pub mod models;
 pub mod repositories;
 pub mod requests;
 pub mod resources;
 pub mod schema;
+pub mod tasks;
 pub mod validators;
 
 pub fn establish_connection() -> PgConnection {
@@ -47,6 +51,28 @@ pub fn calculate_hash<T: Hash>(t: &T) -> u64 {
     hasher.finish()
 }
 
+/// Reads the spell versions a client allows to be changed from its `If-Match` header. `None`
+/// means any version will do, either because the header is missing or because it is `*`. Weak
+/// and malformed tags never match, so they leave nothing to change.
+pub fn if_match(headers: &HeaderMap) -> Option<Vec<i32>> {
+    let values: Vec<&str> = headers
+        .get_all(header::IF_MATCH)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .collect();
+    if values.is_empty() || values.contains(&"*") {
+        return None;
+    }
+    Some(
+        values
+            .iter()
+            .filter_map(|value| value.strip_prefix('"')?.strip_suffix('"')?.parse().ok())
+            .collect(),
+    )
+}
+
 pub fn authenticate(conn: &mut PgConnection, headers: HeaderMap) -> Result<i32, AuthError> {
     if !headers.contains_key("key") {
         return Err(AuthError::AuthError);
@@ -62,3 +88,43 @@ pub fn authenticate(conn: &mut PgConnection, headers: HeaderMap) -> Result<i32,
         Err(_) => Err(AuthError::AuthError),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+
+    use super::*;
+
+    fn headers(values: &[&'static str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append(header::IF_MATCH, HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn if_match_allows_any_version_without_the_header() {
+        assert_eq!(if_match(&headers(&[])), None);
+    }
+
+    #[test]
+    fn if_match_allows_any_version_for_a_wildcard() {
+        assert_eq!(if_match(&headers(&["*"])), None);
+        assert_eq!(if_match(&headers(&["\"3\", *"])), None);
+    }
+
+    #[test]
+    fn if_match_reads_listed_versions() {
+        assert_eq!(if_match(&headers(&["\"3\""])), Some(vec![3]));
+        assert_eq!(if_match(&headers(&["\"3\", \"4\""])), Some(vec![3, 4]));
+        assert_eq!(if_match(&headers(&["\"3\"", " \"5\" "])), Some(vec![3, 5]));
+    }
+
+    #[test]
+    fn if_match_never_matches_weak_or_malformed_tags() {
+        assert_eq!(if_match(&headers(&["W/\"3\""])), Some(vec![]));
+        assert_eq!(if_match(&headers(&["3", "\"three\"", "\"3"])), Some(vec![]));
+        assert_eq!(if_match(&headers(&["W/\"3\", \"4\""])), Some(vec![4]));
+    }
+}
@@ -1,12 +1,18 @@
 use axum::{
+    extract::DefaultBodyLimit,
     middleware,
-    routing::{get, patch, post},
+    routing::{get, patch, post, put},
     Router,
 };
 use spellbook_api::handlers::{
+    images::{delete_spell_image, get_image, put_spell_image},
+    revisions::{get_spell_revision, get_spell_revision_diff, get_spell_revisions, revert_spell},
+    schools::{delete_school, get_schools, post_school, update_school},
     spells::{
-        copy_public_spell, delete_spell, get_spell, get_spells, post_spell, publish_spell,
-        query_public_spells, query_spells, unpublish_spell, update_spell,
+        add_spell_tags, copy_public_spell, delete_spell, empty_trash, get_spell, get_spell_effect,
+        get_spells, get_tags, get_trash, patch_spell, post_spell, preview_description,
+        publish_spell, query_public_spells, query_spells, remove_spell_tags, restore_spell,
+        suggest_spells, unpublish_spell, update_spell,
     },
     users::post_user,
 };
@@ -20,18 +26,48 @@ async fn main() {
     let app = Router::new()
         .route("/spells", get(get_spells).post(post_spell))
         .route("/spells/query", post(query_spells))
+        .route("/spells/suggest", get(suggest_spells))
         .route(
             "/spell/:nanoid",
-            get(get_spell).put(update_spell).delete(delete_spell),
+            get(get_spell)
+                .put(update_spell)
+                .patch(patch_spell)
+                .delete(delete_spell),
         )
+        .route("/spell/effect/:nanoid", get(get_spell_effect))
+        .route("/spell/description/preview", post(preview_description))
+        .route(
+            "/spell/image/:nanoid",
+            put(put_spell_image)
+                .delete(delete_spell_image)
+                // leaves room for the rest of the multipart body around the image
+                .layer(DefaultBodyLimit::max(
+                    spellbook_api::images::MAX_IMAGE_BYTES + 64 * 1024,
+                )),
+        )
+        .route("/spell/revisions/:nanoid", get(get_spell_revisions))
+        .route("/spell/revisions/diff/:nanoid", get(get_spell_revision_diff))
+        .route("/spell/revision/:nanoid/:revision", get(get_spell_revision))
+        .route("/spell/revert/:nanoid/:revision", patch(revert_spell))
+        .route("/spell/tags/add/:nanoid", patch(add_spell_tags))
+        .route("/spell/tags/remove/:nanoid", patch(remove_spell_tags))
+        .route("/tags", get(get_tags))
+        .route("/schools", get(get_schools).post(post_school))
+        .route("/school/:name", put(update_school).delete(delete_school))
         .route("/spell/publish/:nanoid", patch(publish_spell))
-        .route("/spell/unpublish", patch(unpublish_spell))
+        .route("/spell/unpublish/:nanoid", patch(unpublish_spell))
+        .route("/trash", get(get_trash).delete(empty_trash))
+        .route("/trash/restore/:nanoid", patch(restore_spell))
         .route("/public/spells/query", post(query_public_spells))
         .route("/public/spell/copy/:nanoid", patch(copy_public_spell))
         .layer(middleware::from_fn(spellbook_api::middleware::auth))
         .route("/users", post(post_user))
+        .route("/images/:file_name", get(get_image))
         .layer(cors_layer);
 
+    tokio::spawn(spellbook_api::tasks::purge_trash());
+    tokio::spawn(spellbook_api::tasks::remove_unused_images());
+
     let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
     println!("listening on port 3000...");
     axum::serve(listener, app).await.unwrap();
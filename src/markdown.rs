@@ -0,0 +1,22 @@
+use pulldown_cmark::{html, Options, Parser};
+
+/// Renders a CommonMark spell description to HTML. Descriptions of public spells come from other
+/// users, so the HTML is sanitized against XSS before it is handed out.
+pub fn render_description(description: &str) -> String {
+    // tables and strikethrough are common in spell descriptions but not part of CommonMark
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH;
+    let parser = Parser::new_ext(description, options);
+
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, parser);
+    ammonia::clean(&unsafe_html)
+}
+
+/// Sanitizes a search snippet of a description, which is raw markdown with the matched words
+/// wrapped in `<mark>` tags. Only those tags are kept, everything else is escaped.
+pub fn clean_snippet(snippet: &str) -> String {
+    ammonia::Builder::empty()
+        .add_tags(["mark"])
+        .clean(snippet)
+        .to_string()
+}
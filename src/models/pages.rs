@@ -0,0 +1,9 @@
+use crate::requests::pages::Cursor;
+
+/// One page of a listing. The cursor is only set if there are more items after this page, the
+/// total only if it was asked for.
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<Cursor>,
+    pub total: Option<i64>,
+}
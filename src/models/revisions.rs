@@ -0,0 +1,168 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    models::{
+        spells::{Spell, SpellDetails},
+        users::User,
+    },
+    schema::spell_revisions,
+};
+
+#[derive(Queryable, Selectable, Identifiable, Associations, Debug)]
+#[diesel(table_name = spell_revisions)]
+#[diesel(belongs_to(Spell))]
+#[diesel(belongs_to(User))]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct SpellRevision {
+    pub id: i32,
+    pub spell_id: i32,
+    pub revision: i32,
+    pub user_id: i32,
+    pub snapshot: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+impl SpellRevision {
+    pub fn snapshot(&self) -> Result<SpellSnapshot, serde_json::Error> {
+        SpellSnapshot::deserialize(&self.snapshot)
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = spell_revisions)]
+pub struct NewSpellRevision {
+    pub spell_id: i32,
+    pub revision: i32,
+    pub user_id: i32,
+    pub snapshot: serde_json::Value,
+}
+
+/// A revision together with the user who made it.
+pub type SpellRevisionDetails = (SpellRevision, User);
+
+/// Everything about a spell a user can change, as it was at one revision. The school is kept by
+/// name for display and by id to revert to the same homebrew school even if it was renamed.
+#[derive(Serialize, Deserialize, PartialEq, Debug)]
+pub struct SpellSnapshot {
+    pub name: String,
+    pub level: i32,
+    pub casting_time_unit: String,
+    pub casting_time_quantity: i32,
+    pub reaction_trigger: Option<String>,
+    pub magic_school: String,
+    pub homebrew_school_id: Option<i32>,
+    pub concentration: bool,
+    pub range_type: String,
+    pub range_distance: Option<i32>,
+    pub range_unit: Option<String>,
+    pub area_shape: Option<String>,
+    pub area_size: Option<i32>,
+    pub duration_unit: String,
+    pub duration_quantity: Option<i32>,
+    pub duration_up_to: bool,
+    pub description: String,
+    pub description_html: Option<String>,
+    pub verbal: bool,
+    pub somatic: bool,
+    pub material: bool,
+    pub material_description: Option<String>,
+    pub material_cost: Option<i32>,
+    pub material_consumed: bool,
+    pub ritual: bool,
+    pub damage_dice: Option<String>,
+    pub higher_levels: Option<String>,
+    pub higher_level_dice: Option<String>,
+    pub cantrip_scaling: bool,
+    pub healing_dice: Option<String>,
+    pub damage_types: Vec<String>,
+    pub save_ability: Option<String>,
+    pub attack_type: Option<String>,
+    pub conditions: Vec<String>,
+    pub source: Option<String>,
+    pub page: Option<i32>,
+    pub is_homebrew: bool,
+    pub license: Option<String>,
+    pub license_label: Option<String>,
+    pub classes: Vec<String>,
+    pub tags: Vec<String>,
+}
+
+impl SpellSnapshot {
+    pub fn from_details(details: &SpellDetails) -> Self {
+        let (spell, classes, tags, homebrew_school) = details;
+        SpellSnapshot {
+            name: spell.name.to_string(),
+            level: spell.level,
+            casting_time_unit: spell.casting_time_unit.to_string(),
+            casting_time_quantity: spell.casting_time_quantity,
+            reaction_trigger: spell.reaction_trigger.clone(),
+            magic_school: match (spell.magic_school, homebrew_school) {
+                (Some(magic_school), _) => magic_school.to_string(),
+                (None, Some(homebrew_school)) => homebrew_school.name.to_string(),
+                (None, None) => String::new(),
+            },
+            homebrew_school_id: spell.homebrew_school_id,
+            concentration: spell.concentration,
+            range_type: spell.range_type.to_string(),
+            range_distance: spell.range_distance,
+            range_unit: spell.range_unit.clone(),
+            area_shape: spell.area_shape.clone(),
+            area_size: spell.area_size,
+            duration_unit: spell.duration_unit.to_string(),
+            duration_quantity: spell.duration_quantity,
+            duration_up_to: spell.duration_up_to,
+            description: spell.description.to_string(),
+            description_html: spell.description_html.clone(),
+            verbal: spell.verbal,
+            somatic: spell.somatic,
+            material: spell.material,
+            material_description: spell.material_description.clone(),
+            material_cost: spell.material_cost,
+            material_consumed: spell.material_consumed,
+            ritual: spell.ritual,
+            damage_dice: spell.damage_dice.clone(),
+            higher_levels: spell.higher_levels.clone(),
+            higher_level_dice: spell.higher_level_dice.clone(),
+            cantrip_scaling: spell.cantrip_scaling,
+            healing_dice: spell.healing_dice.clone(),
+            damage_types: spell.damage_types.clone(),
+            save_ability: spell.save_ability.clone(),
+            attack_type: spell.attack_type.clone(),
+            conditions: spell.conditions.clone(),
+            source: spell.source.clone(),
+            page: spell.page,
+            is_homebrew: spell.is_homebrew,
+            license: spell.license.clone(),
+            license_label: spell.license_label.clone(),
+            classes: classes.iter().map(|c| c.caster_class.to_string()).collect(),
+            tags: tags.iter().map(|t| t.tag.to_string()).collect(),
+        }
+    }
+
+    /// Lists the fields that differ between this snapshot and a later one. The rendered
+    /// description and the school id only follow other fields, so they are left out.
+    pub fn diff(&self, later: &SpellSnapshot) -> Result<Vec<FieldChange>, serde_json::Error> {
+        let serde_json::Value::Object(from) = serde_json::to_value(self)? else {
+            return Ok(Vec::new());
+        };
+        let serde_json::Value::Object(mut to) = serde_json::to_value(later)? else {
+            return Ok(Vec::new());
+        };
+        Ok(from
+            .into_iter()
+            .filter(|(field, _)| field != "description_html" && field != "homebrew_school_id")
+            .filter_map(|(field, from)| {
+                let to = to.remove(&field)?;
+                (from != to).then_some(FieldChange { field, from, to })
+            })
+            .collect())
+    }
+}
+
+pub struct FieldChange {
+    pub field: String,
+    pub from: serde_json::Value,
+    pub to: serde_json::Value,
+}
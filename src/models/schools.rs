@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+
+use crate::{
+    models::users::User, requests::schools::UpdateHomebrewSchoolRequest, schema::homebrew_schools,
+};
+
+#[derive(Queryable, Selectable, Identifiable, Associations, Clone, Debug)]
+#[diesel(table_name = homebrew_schools)]
+#[diesel(belongs_to(User))]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct HomebrewSchool {
+    pub id: i32,
+    pub user_id: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub shared: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = homebrew_schools)]
+pub struct NewHomebrewSchool<'a> {
+    pub user_id: i32,
+    pub name: &'a str,
+    pub description: Option<&'a str>,
+    pub shared: bool,
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name = homebrew_schools)]
+pub struct UpdatedHomebrewSchool<'a> {
+    pub name: Option<&'a str>,
+    pub description: Option<Option<&'a str>>,
+    pub shared: Option<bool>,
+}
+
+impl<'a> UpdatedHomebrewSchool<'a> {
+    pub fn from_request(request: &'a UpdateHomebrewSchoolRequest) -> Self {
+        UpdatedHomebrewSchool {
+            name: request.name.as_deref().map(str::trim),
+            description: request.description.as_deref().map(Some),
+            shared: request.shared,
+        }
+    }
+}
@@ -1,39 +1,225 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
 use diesel::prelude::*;
 
-use crate::{requests::spells::UpdateSpellRequest, schema::spells};
+use crate::{
+    dice::Dice,
+    enums::MagicSchool,
+    errors::SpellEffectError,
+    markdown::render_description,
+    models::{revisions::SpellSnapshot, schools::HomebrewSchool, users::User},
+    requests::spells::UpdateSpellRequest,
+    schema::{spell_classes, spell_tags, spells},
+};
 
-#[derive(Queryable, Selectable, Debug)]
+#[derive(Queryable, Selectable, Identifiable, Debug)]
 #[diesel(table_name = crate::schema::spells)]
 #[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct Spell {
     pub id: i32,
     pub name: String,
-    pub level: String,
-    pub casting_time: String,
-    pub magic_school: String,
+    pub level: i32,
+    pub casting_time_unit: String,
+    pub casting_time_quantity: i32,
+    pub reaction_trigger: Option<String>,
+    pub magic_school: Option<MagicSchool>,
     pub concentration: bool,
-    pub range: String,
-    pub duration: String,
+    pub range_type: String,
+    pub range_distance: Option<i32>,
+    pub range_unit: Option<String>,
+    pub area_shape: Option<String>,
+    pub area_size: Option<i32>,
+    pub duration_unit: String,
+    pub duration_quantity: Option<i32>,
+    pub duration_up_to: bool,
     pub description: String,
     pub user_id: i32,
     pub published: bool,
     pub nanoid: String,
+    pub verbal: bool,
+    pub somatic: bool,
+    pub material: bool,
+    pub material_description: Option<String>,
+    pub material_cost: Option<i32>,
+    pub material_consumed: bool,
+    pub ritual: bool,
+    pub damage_dice: Option<String>,
+    pub higher_levels: Option<String>,
+    pub higher_level_dice: Option<String>,
+    pub cantrip_scaling: bool,
+    pub healing_dice: Option<String>,
+    pub damage_types: Vec<String>,
+    pub save_ability: Option<String>,
+    pub attack_type: Option<String>,
+    pub conditions: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub source: Option<String>,
+    pub page: Option<i32>,
+    pub is_homebrew: bool,
+    pub license: Option<String>,
+    pub license_label: Option<String>,
+    pub homebrew_school_id: Option<i32>,
+    pub description_html: Option<String>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub version: i32,
+    pub image: Option<String>,
+    pub copied_from_id: Option<i32>,
+}
+
+impl Spell {
+    /// The entity tag of the current version of the spell, sent back in `If-Match` to make sure
+    /// a change is based on it.
+    pub fn etag(&self) -> String {
+        format!("\"{}\"", self.version)
+    }
+
+    /// Computes the damage and healing dice of the spell when cast with a spell slot of
+    /// `slot_level` or, for cantrips, by a character of `character_level`. Both default to the
+    /// lowest level.
+    pub fn effect(
+        &self,
+        slot_level: Option<i32>,
+        character_level: Option<i32>,
+    ) -> Result<SpellEffect, SpellEffectError> {
+        let damage_dice = self.damage_dice.as_deref().map(parse_dice).transpose()?;
+        let healing_dice = self.healing_dice.as_deref().map(parse_dice).transpose()?;
+        let Some(effect_dice) = damage_dice.or(healing_dice) else {
+            return Err(SpellEffectError::NoEffectDice(self.name.to_string()));
+        };
+
+        if self.level == 0 {
+            let character_level = character_level.unwrap_or(1);
+            if !(1..=20).contains(&character_level) {
+                return Err(SpellEffectError::InvalidCharacterLevel(character_level));
+            }
+            let factor = match self.cantrip_scaling {
+                true => cantrip_tier(character_level),
+                false => 1,
+            };
+            let multiply = |dice: Dice| {
+                dice.multiplied_by(factor)
+                    .ok_or_else(|| SpellEffectError::TooManyDice(self.name.to_string()))
+            };
+            return Ok(SpellEffect {
+                slot_level: None,
+                character_level: Some(character_level),
+                damage_dice: damage_dice.map(multiply).transpose()?,
+                healing_dice: healing_dice.map(multiply).transpose()?,
+            });
+        }
+
+        let slot_level = slot_level.unwrap_or(self.level);
+        if !(self.level..=9).contains(&slot_level) {
+            return Err(SpellEffectError::InvalidSlotLevel(slot_level, self.level));
+        }
+        let (damage_dice, healing_dice) = match &self.higher_level_dice {
+            Some(higher_level_dice) => {
+                let increment = parse_dice(higher_level_dice)?;
+                let times = (slot_level - self.level) as u32;
+                // the higher level dice only add to the dice rolling the same die
+                let increase = |dice: Dice| match dice.sides == increment.sides {
+                    true => dice
+                        .increased_by(increment, times)
+                        .ok_or_else(|| SpellEffectError::TooManyDice(self.name.to_string())),
+                    false => Ok(dice),
+                };
+                if damage_dice
+                    .into_iter()
+                    .chain(healing_dice)
+                    .all(|dice| dice.sides != increment.sides)
+                {
+                    return Err(SpellEffectError::MismatchedHigherLevelDice(
+                        higher_level_dice.to_string(),
+                        effect_dice.to_string(),
+                    ));
+                }
+                (
+                    damage_dice.map(increase).transpose()?,
+                    healing_dice.map(increase).transpose()?,
+                )
+            }
+            None => (damage_dice, healing_dice),
+        };
+        Ok(SpellEffect {
+            slot_level: Some(slot_level),
+            character_level: None,
+            damage_dice,
+            healing_dice,
+        })
+    }
+}
+
+fn parse_dice(dice: &str) -> Result<Dice, SpellEffectError> {
+    dice.parse()
+        .map_err(|_| SpellEffectError::InvalidDice(dice.to_string()))
+}
+
+/// Cantrips get stronger when their caster reaches level 5, 11 and 17.
+fn cantrip_tier(character_level: i32) -> u32 {
+    match character_level {
+        ..=4 => 1,
+        5..=10 => 2,
+        11..=16 => 3,
+        _ => 4,
+    }
+}
+
+pub struct SpellEffect {
+    pub slot_level: Option<i32>,
+    pub character_level: Option<i32>,
+    pub damage_dice: Option<Dice>,
+    pub healing_dice: Option<Dice>,
 }
 
 #[derive(Insertable)]
 #[diesel(table_name = spells)]
 pub struct NewSpell<'a> {
     pub name: &'a str,
-    pub level: &'a str,
-    pub casting_time: &'a str,
-    pub magic_school: &'a str,
+    pub level: i32,
+    pub casting_time_unit: &'a str,
+    pub casting_time_quantity: i32,
+    pub reaction_trigger: Option<&'a str>,
+    pub magic_school: Option<MagicSchool>,
     pub concentration: bool,
-    pub range: &'a str,
-    pub duration: &'a str,
+    pub range_type: &'a str,
+    pub range_distance: Option<i32>,
+    pub range_unit: Option<&'a str>,
+    pub area_shape: Option<&'a str>,
+    pub area_size: Option<i32>,
+    pub duration_unit: &'a str,
+    pub duration_quantity: Option<i32>,
+    pub duration_up_to: bool,
     pub description: &'a str,
     pub user_id: i32,
     pub published: bool,
     pub nanoid: &'a str,
+    pub verbal: bool,
+    pub somatic: bool,
+    pub material: bool,
+    pub material_description: Option<&'a str>,
+    pub material_cost: Option<i32>,
+    pub material_consumed: bool,
+    pub ritual: bool,
+    pub damage_dice: Option<&'a str>,
+    pub higher_levels: Option<&'a str>,
+    pub higher_level_dice: Option<&'a str>,
+    pub cantrip_scaling: bool,
+    pub healing_dice: Option<&'a str>,
+    pub damage_types: &'a [String],
+    pub save_ability: Option<&'a str>,
+    pub attack_type: Option<&'a str>,
+    pub conditions: &'a [String],
+    pub source: Option<&'a str>,
+    pub page: Option<i32>,
+    pub is_homebrew: bool,
+    pub license: Option<&'a str>,
+    pub license_label: Option<&'a str>,
+    pub homebrew_school_id: Option<i32>,
+    pub description_html: String,
+    pub image: Option<&'a str>,
+    pub copied_from_id: Option<i32>,
 }
 
 #[derive(AsChangeset)]
@@ -41,26 +227,201 @@ pub struct NewSpell<'a> {
 #[diesel(belongs_to(users))]
 pub struct UpdatedSpell<'a> {
     pub name: Option<&'a str>,
-    pub level: Option<&'a str>,
-    pub casting_time: Option<&'a str>,
-    pub magic_school: Option<&'a str>,
+    pub level: Option<i32>,
+    pub casting_time_unit: Option<&'a str>,
+    pub casting_time_quantity: Option<i32>,
+    pub reaction_trigger: Option<Option<&'a str>>,
+    pub magic_school: Option<Option<MagicSchool>>,
     pub concentration: Option<bool>,
-    pub range: Option<&'a str>,
-    pub duration: Option<&'a str>,
+    pub range_type: Option<&'a str>,
+    pub range_distance: Option<Option<i32>>,
+    pub range_unit: Option<Option<&'a str>>,
+    pub area_shape: Option<Option<&'a str>>,
+    pub area_size: Option<Option<i32>>,
+    pub duration_unit: Option<&'a str>,
+    pub duration_quantity: Option<Option<i32>>,
+    pub duration_up_to: Option<bool>,
     pub description: Option<&'a str>,
+    pub verbal: Option<bool>,
+    pub somatic: Option<bool>,
+    pub material: Option<bool>,
+    pub material_description: Option<Option<&'a str>>,
+    pub material_cost: Option<Option<i32>>,
+    pub material_consumed: Option<bool>,
+    pub ritual: Option<bool>,
+    pub damage_dice: Option<Option<&'a str>>,
+    pub higher_levels: Option<Option<&'a str>>,
+    pub higher_level_dice: Option<Option<&'a str>>,
+    pub cantrip_scaling: Option<bool>,
+    pub healing_dice: Option<Option<&'a str>>,
+    pub damage_types: Option<&'a [String]>,
+    pub save_ability: Option<Option<&'a str>>,
+    pub attack_type: Option<Option<&'a str>>,
+    pub conditions: Option<&'a [String]>,
+    pub source: Option<Option<&'a str>>,
+    pub page: Option<Option<i32>>,
+    pub is_homebrew: Option<bool>,
+    pub license: Option<Option<&'a str>>,
+    pub license_label: Option<Option<&'a str>>,
+    pub homebrew_school_id: Option<Option<i32>>,
+    pub description_html: Option<String>,
+}
+
+impl<'a> UpdatedSpell<'a> {
+    /// Replaces every field of a spell, `homebrew_school_id` is the id of the homebrew school the
+    /// request names, if it names one.
+    pub fn from_request(request: &'a UpdateSpellRequest, homebrew_school_id: Option<i32>) -> Self {
+        UpdatedSpell {
+            name: Some(&request.name),
+            level: Some(request.level),
+            casting_time_unit: Some(&request.casting_time_unit),
+            casting_time_quantity: Some(request.casting_time_quantity.unwrap_or(1)),
+            reaction_trigger: Some(request.reaction_trigger.as_deref()),
+            magic_school: Some(request.magic_school.standard()),
+            concentration: Some(request.concentration),
+            range_type: Some(&request.range_type),
+            range_distance: Some(request.range_distance),
+            range_unit: Some(request.range_unit()),
+            area_shape: Some(request.area_shape.as_deref()),
+            area_size: Some(request.area_size),
+            duration_unit: Some(&request.duration_unit),
+            duration_quantity: Some(request.duration_quantity),
+            duration_up_to: Some(request.duration_up_to),
+            description: Some(&request.description),
+            verbal: Some(request.verbal),
+            somatic: Some(request.somatic),
+            material: Some(request.material),
+            material_description: Some(request.material_description.as_deref()),
+            material_cost: Some(request.material_cost),
+            material_consumed: Some(request.material_consumed),
+            ritual: Some(request.ritual),
+            damage_dice: Some(request.damage_dice.as_deref()),
+            higher_levels: Some(request.higher_levels.as_deref()),
+            higher_level_dice: Some(request.higher_level_dice.as_deref()),
+            cantrip_scaling: Some(request.cantrip_scaling),
+            healing_dice: Some(request.healing_dice.as_deref()),
+            damage_types: Some(&request.damage_types),
+            save_ability: Some(request.save_ability.as_deref()),
+            attack_type: Some(request.attack_type.as_deref()),
+            conditions: Some(&request.conditions),
+            source: Some(request.source.as_deref()),
+            page: Some(request.page),
+            is_homebrew: Some(request.is_homebrew),
+            license: Some(request.license.as_deref()),
+            license_label: Some(request.license_label.as_deref()),
+            homebrew_school_id: Some(homebrew_school_id),
+            description_html: Some(render_description(&request.description)),
+        }
+    }
 }
 
 impl<'a> UpdatedSpell<'a> {
-    pub fn from_request(request: &'a UpdateSpellRequest) -> Self {
+    /// Restores every field of a spell to a snapshot taken at an earlier revision.
+    pub fn from_snapshot(snapshot: &'a SpellSnapshot) -> Self {
+        let magic_school = match snapshot.homebrew_school_id {
+            Some(_) => None,
+            None => MagicSchool::from_str(&snapshot.magic_school).ok(),
+        };
         UpdatedSpell {
-            name: request.name.as_deref(),
-            level: request.level.as_deref(),
-            casting_time: request.casting_time.as_deref(),
-            magic_school: request.magic_school.as_deref(),
-            concentration: request.concentration,
-            range: request.range.as_deref(),
-            duration: request.duration.as_deref(),
-            description: request.description.as_deref(),
+            name: Some(&snapshot.name),
+            level: Some(snapshot.level),
+            casting_time_unit: Some(&snapshot.casting_time_unit),
+            casting_time_quantity: Some(snapshot.casting_time_quantity),
+            reaction_trigger: Some(snapshot.reaction_trigger.as_deref()),
+            magic_school: Some(magic_school),
+            concentration: Some(snapshot.concentration),
+            range_type: Some(&snapshot.range_type),
+            range_distance: Some(snapshot.range_distance),
+            range_unit: Some(snapshot.range_unit.as_deref()),
+            area_shape: Some(snapshot.area_shape.as_deref()),
+            area_size: Some(snapshot.area_size),
+            duration_unit: Some(&snapshot.duration_unit),
+            duration_quantity: Some(snapshot.duration_quantity),
+            duration_up_to: Some(snapshot.duration_up_to),
+            description: Some(&snapshot.description),
+            verbal: Some(snapshot.verbal),
+            somatic: Some(snapshot.somatic),
+            material: Some(snapshot.material),
+            material_description: Some(snapshot.material_description.as_deref()),
+            material_cost: Some(snapshot.material_cost),
+            material_consumed: Some(snapshot.material_consumed),
+            ritual: Some(snapshot.ritual),
+            damage_dice: Some(snapshot.damage_dice.as_deref()),
+            higher_levels: Some(snapshot.higher_levels.as_deref()),
+            higher_level_dice: Some(snapshot.higher_level_dice.as_deref()),
+            cantrip_scaling: Some(snapshot.cantrip_scaling),
+            healing_dice: Some(snapshot.healing_dice.as_deref()),
+            damage_types: Some(&snapshot.damage_types),
+            save_ability: Some(snapshot.save_ability.as_deref()),
+            attack_type: Some(snapshot.attack_type.as_deref()),
+            conditions: Some(&snapshot.conditions),
+            source: Some(snapshot.source.as_deref()),
+            page: Some(snapshot.page),
+            is_homebrew: Some(snapshot.is_homebrew),
+            license: Some(snapshot.license.as_deref()),
+            license_label: Some(snapshot.license_label.as_deref()),
+            homebrew_school_id: Some(snapshot.homebrew_school_id),
+            description_html: Some(
+                snapshot
+                    .description_html
+                    .clone()
+                    .unwrap_or_else(|| render_description(&snapshot.description)),
+            ),
         }
     }
 }
+
+#[derive(Queryable, Selectable, Identifiable, Associations, Debug)]
+#[diesel(table_name = spell_classes)]
+#[diesel(primary_key(spell_id, caster_class))]
+#[diesel(belongs_to(Spell))]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct SpellClass {
+    pub spell_id: i32,
+    pub caster_class: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = spell_classes)]
+pub struct NewSpellClass<'a> {
+    pub spell_id: i32,
+    pub caster_class: &'a str,
+}
+
+/// A spell together with its class list, tags and homebrew school.
+pub type SpellDetails = (
+    Spell,
+    Vec<SpellClass>,
+    Vec<SpellTag>,
+    Option<HomebrewSchool>,
+);
+
+/// A published spell together with its author, class list, tags and homebrew school.
+pub type PublicSpellDetails = (
+    Spell,
+    User,
+    Vec<SpellClass>,
+    Vec<SpellTag>,
+    Option<HomebrewSchool>,
+);
+
+/// A queried spell with the parts of its description that matched the search highlighted, if it
+/// was searched for.
+pub type SpellMatch<T> = (T, Option<String>);
+
+#[derive(Queryable, Selectable, Identifiable, Associations, Debug)]
+#[diesel(table_name = spell_tags)]
+#[diesel(primary_key(spell_id, tag))]
+#[diesel(belongs_to(Spell))]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct SpellTag {
+    pub spell_id: i32,
+    pub tag: String,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = spell_tags)]
+pub struct NewSpellTag<'a> {
+    pub spell_id: i32,
+    pub tag: &'a str,
+}
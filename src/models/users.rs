@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use diesel::{
     prelude::{Insertable, Queryable},
     Selectable,
@@ -12,6 +13,8 @@ pub struct User {
     pub id: i32,
     pub username: String,
     pub key_hash: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
 }
 
 #[derive(Insertable)]
@@ -1,2 +1,4 @@
+pub mod revisions;
+pub mod schools;
 pub mod spells;
 pub mod users;
@@ -0,0 +1,77 @@
+use diesel::{
+    ExpressionMethods, OptionalExtension, PgConnection, QueryDsl, RunQueryDsl, SelectableHelper,
+};
+
+use crate::{
+    models::{
+        revisions::{NewSpellRevision, SpellRevision, SpellRevisionDetails, SpellSnapshot},
+        spells::{Spell, SpellDetails},
+        users::User,
+    },
+    schema::{
+        spell_revisions::{self, revision, spell_id},
+        users,
+    },
+};
+
+pub fn get_revisions(
+    conn: &mut PgConnection,
+    spell: &Spell,
+) -> Result<Vec<SpellRevisionDetails>, diesel::result::Error> {
+    spell_revisions::table
+        .inner_join(users::table)
+        .select((SpellRevision::as_select(), User::as_select()))
+        .filter(spell_id.eq(spell.id))
+        .order(revision.desc())
+        .load(conn)
+}
+
+pub fn get_revision(
+    conn: &mut PgConnection,
+    spell: &Spell,
+    spell_revision: i32,
+) -> Result<SpellRevisionDetails, diesel::result::Error> {
+    spell_revisions::table
+        .inner_join(users::table)
+        .select((SpellRevision::as_select(), User::as_select()))
+        .filter(spell_id.eq(spell.id))
+        .filter(revision.eq(spell_revision))
+        .first(conn)
+}
+
+/// Records the current state of a spell as its next revision, unless nothing changed since the
+/// last one.
+pub fn record_revision(
+    conn: &mut PgConnection,
+    details: &SpellDetails,
+) -> Result<(), diesel::result::Error> {
+    let (spell, ..) = details;
+    let snapshot = SpellSnapshot::from_details(details);
+
+    let latest_revision = spell_revisions::table
+        .select(SpellRevision::as_select())
+        .filter(spell_id.eq(spell.id))
+        .order(revision.desc())
+        .first(conn)
+        .optional()?;
+    if let Some(latest_revision) = &latest_revision {
+        if latest_revision
+            .snapshot()
+            .is_ok_and(|latest| latest == snapshot)
+        {
+            return Ok(());
+        }
+    }
+
+    let new_revision = NewSpellRevision {
+        spell_id: spell.id,
+        revision: latest_revision.map_or(1, |latest| latest.revision + 1),
+        user_id: spell.user_id,
+        snapshot: serde_json::to_value(&snapshot)
+            .map_err(|e| diesel::result::Error::SerializationError(Box::new(e)))?,
+    };
+    diesel::insert_into(spell_revisions::table)
+        .values(new_revision)
+        .execute(conn)?;
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use diesel::{
+    define_sql_function,
+    pg::Pg,
+    sql_types::{Int4, Nullable, Text},
+    ExpressionMethods, NullableExpressionMethods, OptionalEmptyChangesetExtension, PgConnection,
+    QueryDsl, RunQueryDsl, SelectableHelper,
+};
+
+use crate::{
+    models::schools::{HomebrewSchool, NewHomebrewSchool, UpdatedHomebrewSchool},
+    schema::homebrew_schools::{self, name, user_id},
+};
+
+define_sql_function!(fn lower(x: Text) -> Text);
+
+pub fn get_schools(
+    conn: &mut PgConnection,
+    u_id: i32,
+) -> Result<Vec<HomebrewSchool>, diesel::result::Error> {
+    homebrew_schools::table
+        .select(HomebrewSchool::as_select())
+        .filter(user_id.eq(u_id))
+        .order(name)
+        .load(conn)
+}
+
+/// Finds one of a user's schools, school names are case insensitive.
+pub fn get_school_by_name(
+    conn: &mut PgConnection,
+    u_id: i32,
+    school_name: &str,
+) -> Result<HomebrewSchool, diesel::result::Error> {
+    homebrew_schools::table
+        .select(HomebrewSchool::as_select())
+        .filter(user_id.eq(u_id))
+        .filter(lower(name).eq(school_name.trim().to_lowercase()))
+        .first(conn)
+}
+
+pub fn get_school(
+    conn: &mut PgConnection,
+    school_id: i32,
+) -> Result<HomebrewSchool, diesel::result::Error> {
+    homebrew_schools::table
+        .select(HomebrewSchool::as_select())
+        .find(school_id)
+        .first(conn)
+}
+
+pub fn get_schools_by_ids(
+    conn: &mut PgConnection,
+    school_ids: &[i32],
+) -> Result<Vec<HomebrewSchool>, diesel::result::Error> {
+    homebrew_schools::table
+        .select(HomebrewSchool::as_select())
+        .filter(homebrew_schools::id.eq_any(school_ids))
+        .load(conn)
+}
+
+/// Lists the ids of all schools with one of the given names, whoever they belong to. Made to
+/// filter the nullable school reference of spells.
+pub fn school_ids_by_names(
+    school_names: &[String],
+) -> homebrew_schools::BoxedQuery<'static, Pg, Nullable<Int4>> {
+    let lowercase_names: Vec<String> = school_names
+        .iter()
+        .map(|school_name| school_name.trim().to_lowercase())
+        .collect();
+    homebrew_schools::table
+        .select(homebrew_schools::id.nullable())
+        .filter(lower(name).eq_any(lowercase_names))
+        .into_boxed()
+}
+
+pub fn insert_school(
+    conn: &mut PgConnection,
+    new_school: NewHomebrewSchool,
+) -> Result<HomebrewSchool, diesel::result::Error> {
+    diesel::insert_into(homebrew_schools::table)
+        .values(new_school)
+        .returning(HomebrewSchool::as_returning())
+        .get_result(conn)
+}
+
+/// Gives a user a copy of someone else's school, or their own school of the same name if they
+/// already have one. Only shared schools are copied with their description.
+pub fn copy_school(
+    conn: &mut PgConnection,
+    u_id: i32,
+    school: &HomebrewSchool,
+) -> Result<HomebrewSchool, diesel::result::Error> {
+    match get_school_by_name(conn, u_id, &school.name) {
+        Err(diesel::result::Error::NotFound) => insert_school(
+            conn,
+            NewHomebrewSchool {
+                user_id: u_id,
+                name: &school.name,
+                description: school.description.as_deref().filter(|_| school.shared),
+                shared: false,
+            },
+        ),
+        result => result,
+    }
+}
+
+pub fn update_school(
+    conn: &mut PgConnection,
+    u_id: i32,
+    school_name: &str,
+    updated_school: UpdatedHomebrewSchool,
+) -> Result<HomebrewSchool, diesel::result::Error> {
+    let school = get_school_by_name(conn, u_id, school_name)?;
+    let updated = diesel::update(&school)
+        .set(updated_school)
+        .returning(HomebrewSchool::as_returning())
+        .get_result(conn)
+        .optional_empty_changeset()?;
+    Ok(updated.unwrap_or(school))
+}
+
+pub fn delete_school(
+    conn: &mut PgConnection,
+    u_id: i32,
+    school_name: &str,
+) -> Result<usize, diesel::result::Error> {
+    diesel::delete(homebrew_schools::table)
+        .filter(user_id.eq(u_id))
+        .filter(lower(name).eq(school_name.trim().to_lowercase()))
+        .execute(conn)
+}
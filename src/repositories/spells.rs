@@ -1,28 +1,115 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
 use diesel::{
-    ExpressionMethods, PgConnection, PgTextExpressionMethods, QueryDsl, RunQueryDsl,
+    define_sql_function,
+    dsl::{count_star, not, now},
+    helper_types::{InnerJoin, InnerJoinQuerySource, IntoBoxed},
+    infix_operator,
+    pg::Pg,
+    query_builder::QueryId,
+    sql_types::{Bool, Float4, Int4, Nullable, SqlType, Text, Timestamptz},
+    BelongingToDsl, BoolExpressionMethods, BoxableExpression, Connection, ExpressionMethods,
+    GroupedBy, IntoSql, NullableExpressionMethods, OptionalEmptyChangesetExtension,
+    PgArrayExpressionMethods, PgConnection, PgTextExpressionMethods, QueryDsl, RunQueryDsl,
     SelectableHelper,
 };
 
 use crate::{
+    enums::MagicSchool,
     models::{
-        spells::{NewSpell, Spell, UpdatedSpell},
+        pages::Page,
+        revisions::SpellSnapshot,
+        schools::HomebrewSchool,
+        spells::{
+            NewSpell, NewSpellClass, NewSpellTag, PublicSpellDetails, Spell, SpellClass,
+            SpellDetails, SpellMatch, SpellTag, UpdatedSpell,
+        },
         users::User,
     },
-    requests::spells::{QueryPublicSpellsRequest, QuerySpellsRequest},
+    repositories,
+    requests::{
+        filters::{FieldKind, Filter, FilterField, FilterOperator, FilterValue},
+        pages::{Cursor, PageRequest, SortValue},
+        spells::{
+            ClassMatch, QueryPublicSpellsRequest, QuerySpellsRequest, SortBy, SortKey, SortOrder,
+            SpellFilters, SpellSchool, SpellSearch,
+        },
+    },
     schema::{
+        spell_classes::{self, caster_class},
+        spell_tags::{self, tag},
         spells::{
-            self, casting_time, concentration, duration, level, magic_school, name, nanoid,
-            published, range, user_id,
+            self, area_shape, attack_type, casting_time_quantity, casting_time_unit, concentration,
+            conditions, created_at, damage_types, deleted_at, description, duration_seconds,
+            duration_unit, healing_dice, homebrew_school_id, image, is_homebrew, level, license,
+            magic_school, material, material_cost, name, nanoid, published, range_feet, range_type,
+            ritual, save_ability, search_vector, somatic, source, updated_at, user_id, verbal,
+            version,
         },
+        sql_types,
         users::{self, username},
     },
 };
 
-pub fn get_spells(conn: &mut PgConnection, u_id: i32) -> Result<Vec<Spell>, diesel::result::Error> {
-    spells::table
-        .select(Spell::as_select())
-        .filter(user_id.eq(u_id))
-        .load(conn)
+define_sql_function!(
+    fn spell_school_name(school: Nullable<sql_types::MagicSchool>, school_id: Nullable<Int4>)
+        -> Text
+);
+define_sql_function!(fn spell_copy_count(id: Int4) -> BigInt);
+
+#[derive(QueryId, SqlType)]
+#[diesel(postgres_type(name = "tsquery"))]
+pub struct Tsquery;
+
+infix_operator!(Matches, " @@ ", backend: Pg);
+define_sql_function!(fn spell_search_query(search: Text) -> Tsquery);
+define_sql_function!(fn spell_search_headline(document: Text, query: Tsquery) -> Text);
+define_sql_function!(fn ts_rank(vector: sql_types::Tsvector, query: Tsquery) -> Float4);
+
+infix_operator!(WordSimilarTo, " <% ", backend: Pg);
+define_sql_function!(fn word_similarity(fuzzy_name: Text, spell_name: Text) -> Float4);
+
+define_sql_function!(fn coalesce(condition: Nullable<Bool>, otherwise: Bool) -> Bool);
+
+/// Spells joined with their authors, all listings of spells are queried from them.
+type SpellSource = InnerJoinQuerySource<spells::table, users::table>;
+type SpellQuery = IntoBoxed<'static, InnerJoin<spells::table, users::table>, Pg>;
+type SpellExpression<T> = Box<dyn BoxableExpression<SpellSource, Pg, SqlType = T>>;
+type SpellPredicate = SpellExpression<Bool>;
+/// Conditions of filter trees are unknown for spells without the field they test.
+type FilterPredicate = SpellExpression<Nullable<Bool>>;
+
+pub fn get_spells(
+    conn: &mut PgConnection,
+    u_id: i32,
+    sort: &[SortKey],
+    page: &PageRequest,
+) -> Result<Page<SpellDetails>, diesel::result::Error> {
+    let query = || {
+        spells::table
+            .inner_join(users::table)
+            .into_boxed()
+            .filter(user_id.eq(u_id))
+            .filter(deleted_at.is_null())
+    };
+    let total = if page.total {
+        Some(query().count().get_result(conn)?)
+    } else {
+        None
+    };
+    let spells = load_page(conn, query(), sort, &SpellSearch::default(), page)?;
+    let next_cursor = spells.next_cursor;
+    let spells = spells
+        .items
+        .into_iter()
+        .map(|(spell, _, _)| spell)
+        .collect();
+    Ok(Page {
+        items: with_details(conn, spells)?,
+        next_cursor,
+        total,
+    })
 }
 
 pub fn get_spell_by_nanoid(
@@ -34,6 +121,7 @@ pub fn get_spell_by_nanoid(
         .select(Spell::as_select())
         .filter(user_id.eq(u_id))
         .filter(nanoid.eq(n_id))
+        .filter(deleted_at.is_null())
         .first(conn)
 }
 
@@ -45,42 +133,428 @@ pub fn get_spell_by_name(
     spells::table
         .select(Spell::as_select())
         .filter(user_id.eq(u_id))
-        .filter(name.ilike(spell_name))
+        .filter(name.ilike(escape_like(spell_name)))
+        .filter(deleted_at.is_null())
         .first(conn)
 }
 
 pub fn insert_spell(
     conn: &mut PgConnection,
     new_spell: NewSpell,
-) -> Result<Spell, diesel::result::Error> {
-    diesel::insert_into(spells::table)
-        .values(new_spell)
-        .returning(Spell::as_returning())
-        .get_result(conn)
+    classes: &[String],
+) -> Result<SpellDetails, diesel::result::Error> {
+    conn.transaction(|conn| {
+        let spell = diesel::insert_into(spells::table)
+            .values(new_spell)
+            .returning(Spell::as_returning())
+            .get_result(conn)?;
+        let spell_classes = set_spell_classes(conn, &spell, classes)?;
+        let homebrew_school = get_spell_school(conn, &spell)?;
+        let details = (spell, spell_classes, Vec::new(), homebrew_school);
+        repositories::revisions::record_revision(conn, &details)?;
+        Ok(details)
+    })
 }
 
+/// Updates a spell if its version is one of `versions`, or whatever its version when `versions`
+/// is `None`. A spell with another version is left untouched and reported as not found.
 pub fn update_spell(
     conn: &mut PgConnection,
     u_id: i32,
     n_id: &str,
     updated_spell: UpdatedSpell,
-) -> Result<Spell, diesel::result::Error> {
-    diesel::update(spells::table)
-        .filter(user_id.eq(u_id))
-        .filter(nanoid.eq(n_id))
-        .set(updated_spell)
+    classes: Option<&[String]>,
+    versions: Option<&[i32]>,
+) -> Result<SpellDetails, diesel::result::Error> {
+    conn.transaction(|conn| {
+        let mut query = diesel::update(spells::table)
+            .filter(user_id.eq(u_id))
+            .filter(nanoid.eq(n_id))
+            .filter(deleted_at.is_null())
+            .set(updated_spell)
+            .returning(Spell::as_returning())
+            .into_boxed();
+        if let Some(versions) = versions {
+            query = query.filter(version.eq_any(versions.to_vec()));
+        }
+        let spell = query.get_result(conn).optional_empty_changeset()?;
+
+        // a request that only changes the classes leaves the spell row untouched,
+        // so it has to be marked as updated by hand
+        let spell = match (spell, classes) {
+            (Some(spell), _) => spell,
+            (None, Some(_)) => {
+                let mut query = diesel::update(spells::table)
+                    .filter(user_id.eq(u_id))
+                    .filter(nanoid.eq(n_id))
+                    .filter(deleted_at.is_null())
+                    .set(updated_at.eq(now))
+                    .returning(Spell::as_returning())
+                    .into_boxed();
+                if let Some(versions) = versions {
+                    query = query.filter(version.eq_any(versions.to_vec()));
+                }
+                query.get_result(conn)?
+            }
+            (None, None) => {
+                let spell = get_spell_by_nanoid(conn, u_id, n_id)?;
+                if versions.is_some_and(|versions| !versions.contains(&spell.version)) {
+                    return Err(diesel::result::Error::NotFound);
+                }
+                spell
+            }
+        };
+
+        let spell_classes = match classes {
+            Some(classes) => set_spell_classes(conn, &spell, classes)?,
+            None => get_spell_classes(conn, &spell)?,
+        };
+        let spell_tags = get_spell_tags(conn, &spell)?;
+        let homebrew_school = get_spell_school(conn, &spell)?;
+        let details = (spell, spell_classes, spell_tags, homebrew_school);
+        repositories::revisions::record_revision(conn, &details)?;
+        Ok(details)
+    })
+}
+
+/// Restores a spell to a snapshot of an earlier revision, which is recorded as a new revision.
+/// Like an update, a spell whose version isn't one of `versions` is left untouched and reported
+/// as not found.
+pub fn revert_spell(
+    conn: &mut PgConnection,
+    spell: &Spell,
+    snapshot: &SpellSnapshot,
+    versions: Option<&[i32]>,
+) -> Result<SpellDetails, diesel::result::Error> {
+    conn.transaction(|conn| {
+        let mut query = diesel::update(spell)
+            .set(UpdatedSpell::from_snapshot(snapshot))
+            .returning(Spell::as_returning())
+            .into_boxed();
+        if let Some(versions) = versions {
+            query = query.filter(version.eq_any(versions.to_vec()));
+        }
+        let spell = query.get_result(conn)?;
+        set_spell_classes(conn, &spell, &snapshot.classes)?;
+
+        diesel::delete(spell_tags::table)
+            .filter(spell_tags::spell_id.eq(spell.id))
+            .execute(conn)?;
+        let spell_tags: Vec<NewSpellTag> = snapshot
+            .tags
+            .iter()
+            .map(|snapshot_tag| NewSpellTag {
+                spell_id: spell.id,
+                tag: snapshot_tag,
+            })
+            .collect();
+        diesel::insert_into(spell_tags::table)
+            .values(spell_tags)
+            .execute(conn)?;
+
+        let details = get_spell_details(conn, spell)?;
+        repositories::revisions::record_revision(conn, &details)?;
+        Ok(details)
+    })
+}
+
+/// Loads the class list, tags and homebrew school of a spell.
+pub fn get_spell_details(
+    conn: &mut PgConnection,
+    spell: Spell,
+) -> Result<SpellDetails, diesel::result::Error> {
+    let spell_classes = get_spell_classes(conn, &spell)?;
+    let spell_tags = get_spell_tags(conn, &spell)?;
+    let homebrew_school = get_spell_school(conn, &spell)?;
+    Ok((spell, spell_classes, spell_tags, homebrew_school))
+}
+
+pub fn get_spell_school(
+    conn: &mut PgConnection,
+    spell: &Spell,
+) -> Result<Option<HomebrewSchool>, diesel::result::Error> {
+    spell
+        .homebrew_school_id
+        .map(|school_id| repositories::schools::get_school(conn, school_id))
+        .transpose()
+}
+
+pub fn get_spell_classes(
+    conn: &mut PgConnection,
+    spell: &Spell,
+) -> Result<Vec<SpellClass>, diesel::result::Error> {
+    SpellClass::belonging_to(spell)
+        .select(SpellClass::as_select())
+        .order(caster_class)
+        .load(conn)
+}
+
+pub fn set_spell_classes(
+    conn: &mut PgConnection,
+    spell: &Spell,
+    classes: &[String],
+) -> Result<Vec<SpellClass>, diesel::result::Error> {
+    diesel::delete(spell_classes::table)
+        .filter(spell_classes::spell_id.eq(spell.id))
+        .execute(conn)?;
+
+    let new_spell_classes: Vec<NewSpellClass> = classes
+        .iter()
+        .map(|class| NewSpellClass {
+            spell_id: spell.id,
+            caster_class: class,
+        })
+        .collect();
+
+    diesel::insert_into(spell_classes::table)
+        .values(new_spell_classes)
+        .on_conflict_do_nothing()
+        .execute(conn)?;
+
+    get_spell_classes(conn, spell)
+}
+
+pub fn get_spell_tags(
+    conn: &mut PgConnection,
+    spell: &Spell,
+) -> Result<Vec<SpellTag>, diesel::result::Error> {
+    SpellTag::belonging_to(spell)
+        .select(SpellTag::as_select())
+        .order(tag)
+        .load(conn)
+}
+
+pub fn add_spell_tags(
+    conn: &mut PgConnection,
+    u_id: i32,
+    n_id: &str,
+    tags: &[&str],
+) -> Result<SpellDetails, diesel::result::Error> {
+    conn.transaction(|conn| {
+        let spell = get_spell_by_nanoid(conn, u_id, n_id)?;
+
+        let new_spell_tags: Vec<NewSpellTag> = tags
+            .iter()
+            .map(|new_tag| NewSpellTag {
+                spell_id: spell.id,
+                tag: new_tag,
+            })
+            .collect();
+
+        diesel::insert_into(spell_tags::table)
+            .values(new_spell_tags)
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+
+        touch_spell(conn, spell)
+    })
+}
+
+pub fn remove_spell_tags(
+    conn: &mut PgConnection,
+    u_id: i32,
+    n_id: &str,
+    tags: &[&str],
+) -> Result<SpellDetails, diesel::result::Error> {
+    conn.transaction(|conn| {
+        let spell = get_spell_by_nanoid(conn, u_id, n_id)?;
+
+        diesel::delete(spell_tags::table)
+            .filter(spell_tags::spell_id.eq(spell.id))
+            .filter(tag.eq_any(tags))
+            .execute(conn)?;
+
+        touch_spell(conn, spell)
+    })
+}
+
+/// Marks a spell as updated after its tags changed and records the change as a revision.
+fn touch_spell(
+    conn: &mut PgConnection,
+    spell: Spell,
+) -> Result<SpellDetails, diesel::result::Error> {
+    let spell = diesel::update(&spell)
+        .set(updated_at.eq(now))
         .returning(Spell::as_returning())
-        .get_result(conn)
+        .get_result(conn)?;
+    let details = get_spell_details(conn, spell)?;
+    repositories::revisions::record_revision(conn, &details)?;
+    Ok(details)
+}
+
+/// Lists the tags of a user's spells starting with `prefix`, most used first.
+pub fn get_tag_counts(
+    conn: &mut PgConnection,
+    u_id: i32,
+    prefix: Option<&str>,
+) -> Result<Vec<(String, i64)>, diesel::result::Error> {
+    let mut query = spell_tags::table
+        .inner_join(spells::table)
+        .filter(user_id.eq(u_id))
+        .filter(deleted_at.is_null())
+        .group_by(tag)
+        .select((tag, count_star()))
+        .order((count_star().desc(), tag.asc()))
+        .into_boxed();
+    if let Some(prefix) = prefix {
+        query = query.filter(tag.ilike(format!("{}%", escape_like(prefix))))
+    }
+    query.load(conn)
+}
+
+/// Suggests a user's spells for a partly typed name: names starting with the prefix come first,
+/// then names containing a word similar to it, which catches typos.
+pub fn suggest_spells(
+    conn: &mut PgConnection,
+    u_id: i32,
+    prefix: &str,
+    limit: i64,
+) -> Result<Vec<Spell>, diesel::result::Error> {
+    let starts_with = || name.ilike(format!("{}%", escape_like(prefix)));
+    spells::table
+        .filter(deleted_at.is_null())
+        .filter(user_id.eq(u_id))
+        .filter(starts_with().or(WordSimilarTo::new(prefix.into_sql::<Text>(), name)))
+        .order((
+            starts_with().desc(),
+            word_similarity(prefix, name).desc(),
+            name.asc(),
+        ))
+        .select(Spell::as_select())
+        .limit(limit)
+        .load(conn)
+}
+
+/// Escapes the wildcards of LIKE patterns, so the text only matches itself.
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+fn with_details(
+    conn: &mut PgConnection,
+    spells: Vec<Spell>,
+) -> Result<Vec<SpellDetails>, diesel::result::Error> {
+    let spell_classes = SpellClass::belonging_to(&spells)
+        .select(SpellClass::as_select())
+        .order(caster_class)
+        .load(conn)?
+        .grouped_by(&spells);
+    let spell_tags = SpellTag::belonging_to(&spells)
+        .select(SpellTag::as_select())
+        .order(tag)
+        .load(conn)?
+        .grouped_by(&spells);
+    let school_ids: Vec<i32> = spells.iter().filter_map(|s| s.homebrew_school_id).collect();
+    let homebrew_schools: HashMap<i32, HomebrewSchool> =
+        repositories::schools::get_schools_by_ids(conn, &school_ids)?
+            .into_iter()
+            .map(|school| (school.id, school))
+            .collect();
+
+    Ok(spells
+        .into_iter()
+        .zip(spell_classes)
+        .zip(spell_tags)
+        .map(|((spell, classes), tags)| {
+            let homebrew_school = spell
+                .homebrew_school_id
+                .and_then(|school_id| homebrew_schools.get(&school_id))
+                .cloned();
+            (spell, classes, tags, homebrew_school)
+        })
+        .collect())
+}
+
+/// Splits the schools of a query into the standard schools and the names of homebrew schools.
+fn split_schools(schools: Vec<SpellSchool>) -> (Vec<MagicSchool>, Vec<String>) {
+    let mut standard_schools = Vec::new();
+    let mut homebrew_school_names = Vec::new();
+    for school in schools {
+        match school {
+            SpellSchool::Standard(standard_school) => standard_schools.push(standard_school),
+            SpellSchool::Homebrew(school_name) => homebrew_school_names.push(school_name),
+        }
+    }
+    (standard_schools, homebrew_school_names)
 }
 
+/// Moves a spell to the trash, trashed spells are no longer public. Like updates, it only
+/// happens if the version of the spell is one of `versions`.
 pub fn delete_spell(
     conn: &mut PgConnection,
     u_id: i32,
     n_id: &str,
+    versions: Option<&[i32]>,
 ) -> Result<usize, diesel::result::Error> {
-    diesel::delete(spells::table)
+    let mut query = diesel::update(spells::table)
         .filter(user_id.eq(u_id))
         .filter(nanoid.eq(n_id))
+        .filter(deleted_at.is_null())
+        .set((deleted_at.eq(now), published.eq(false)))
+        .into_boxed();
+    if let Some(versions) = versions {
+        query = query.filter(version.eq_any(versions.to_vec()));
+    }
+    query.execute(conn)
+}
+
+pub fn get_trash(
+    conn: &mut PgConnection,
+    u_id: i32,
+) -> Result<Vec<SpellDetails>, diesel::result::Error> {
+    let spells = spells::table
+        .select(Spell::as_select())
+        .filter(user_id.eq(u_id))
+        .filter(deleted_at.is_not_null())
+        .order(deleted_at.desc())
+        .load(conn)?;
+    with_details(conn, spells)
+}
+
+pub fn get_trashed_spell(
+    conn: &mut PgConnection,
+    u_id: i32,
+    n_id: &str,
+) -> Result<Spell, diesel::result::Error> {
+    spells::table
+        .select(Spell::as_select())
+        .filter(user_id.eq(u_id))
+        .filter(nanoid.eq(n_id))
+        .filter(deleted_at.is_not_null())
+        .first(conn)
+}
+
+pub fn restore_spell(
+    conn: &mut PgConnection,
+    u_id: i32,
+    n_id: &str,
+) -> Result<SpellDetails, diesel::result::Error> {
+    let spell = diesel::update(spells::table)
+        .filter(user_id.eq(u_id))
+        .filter(nanoid.eq(n_id))
+        .filter(deleted_at.is_not_null())
+        .set(deleted_at.eq(None::<DateTime<Utc>>))
+        .returning(Spell::as_returning())
+        .get_result(conn)?;
+    get_spell_details(conn, spell)
+}
+
+pub fn empty_trash(conn: &mut PgConnection, u_id: i32) -> Result<usize, diesel::result::Error> {
+    diesel::delete(spells::table)
+        .filter(user_id.eq(u_id))
+        .filter(deleted_at.is_not_null())
+        .execute(conn)
+}
+
+/// Erases every spell that was trashed before `deleted_before`, whoever it belongs to.
+pub fn purge_trash(
+    conn: &mut PgConnection,
+    deleted_before: DateTime<Utc>,
+) -> Result<usize, diesel::result::Error> {
+    diesel::delete(spells::table)
+        .filter(deleted_at.lt(deleted_before))
         .execute(conn)
 }
 
@@ -89,79 +563,716 @@ pub fn publish_spell(
     u_id: i32,
     n_id: &str,
     publish: bool,
+    versions: Option<&[i32]>,
 ) -> Result<Spell, diesel::result::Error> {
-    diesel::update(spells::table)
+    let mut query = diesel::update(spells::table)
         .filter(user_id.eq(u_id))
         .filter(nanoid.eq(n_id))
+        .filter(deleted_at.is_null())
         .set(published.eq(publish))
         .returning(Spell::as_returning())
-        .get_result(conn)
+        .into_boxed();
+    if let Some(versions) = versions {
+        query = query.filter(version.eq_any(versions.to_vec()));
+    }
+    query.get_result(conn)
+}
+
+/// Attaches an illustration to a spell, or removes it when `file_name` is `None`.
+pub fn set_spell_image(
+    conn: &mut PgConnection,
+    u_id: i32,
+    n_id: &str,
+    file_name: Option<&str>,
+) -> Result<SpellDetails, diesel::result::Error> {
+    let spell = diesel::update(spells::table)
+        .filter(user_id.eq(u_id))
+        .filter(nanoid.eq(n_id))
+        .filter(deleted_at.is_null())
+        .set(image.eq(file_name))
+        .returning(Spell::as_returning())
+        .get_result(conn)?;
+    get_spell_details(conn, spell)
+}
+
+/// The file names of the illustrations of all spells, trashed ones included.
+pub fn get_used_images(conn: &mut PgConnection) -> Result<Vec<String>, diesel::result::Error> {
+    spells::table
+        .select(image.assume_not_null())
+        .filter(image.is_not_null())
+        .distinct()
+        .load(conn)
 }
 
 pub fn query_spells(
     conn: &mut PgConnection,
     u_id: i32,
     query_data: QuerySpellsRequest,
-) -> Result<Vec<Spell>, diesel::result::Error> {
-    let mut query = spells::table.into_boxed();
+    sort: &[SortKey],
+    page: &PageRequest,
+) -> Result<Page<SpellMatch<SpellDetails>>, diesel::result::Error> {
+    let search = query_data.search();
+    let total = if page.total {
+        Some(
+            filter_spells(u_id, query_data.clone())
+                .count()
+                .get_result(conn)?,
+        )
+    } else {
+        None
+    };
+    let spells = load_page(conn, filter_spells(u_id, query_data), sort, &search, page)?;
+    let next_cursor = spells.next_cursor;
+    let (spells, snippets): (Vec<Spell>, Vec<Option<String>>) = spells
+        .items
+        .into_iter()
+        .map(|(spell, _, snippet)| (spell, snippet))
+        .unzip();
+
+    Ok(Page {
+        items: with_details(conn, spells)?
+            .into_iter()
+            .zip(snippets)
+            .collect(),
+        next_cursor,
+        total,
+    })
+}
+
+fn filter_spells(u_id: i32, query_data: QuerySpellsRequest) -> SpellQuery {
+    let mut query = spells::table.inner_join(users::table).into_boxed();
+    query = query.filter(deleted_at.is_null());
     query = query.filter(user_id.eq(u_id));
-    if let Some(query_name) = query_data.name {
+    filter_query(query, query_data.search(), query_data.filters)
+}
+
+pub fn query_public_spells(
+    conn: &mut PgConnection,
+    u_id: i32,
+    query_data: QueryPublicSpellsRequest,
+    sort: &[SortKey],
+    page: &PageRequest,
+) -> Result<Page<SpellMatch<PublicSpellDetails>>, diesel::result::Error> {
+    let search = query_data.search();
+    let total = if page.total {
+        Some(
+            filter_public_spells(u_id, query_data.clone())
+                .count()
+                .get_result(conn)?,
+        )
+    } else {
+        None
+    };
+    let spells = load_page(
+        conn,
+        filter_public_spells(u_id, query_data),
+        sort,
+        &search,
+        page,
+    )?;
+    let next_cursor = spells.next_cursor;
+    let (spells, users): (Vec<Spell>, Vec<(User, Option<String>)>) = spells
+        .items
+        .into_iter()
+        .map(|(spell, user, snippet)| (spell, (user, snippet)))
+        .unzip();
+    let spells_with_details = with_details(conn, spells)?;
+
+    Ok(Page {
+        items: spells_with_details
+            .into_iter()
+            .zip(users)
+            .map(
+                |((spell, classes, tags, homebrew_school), (user, snippet))| {
+                    ((spell, user, classes, tags, homebrew_school), snippet)
+                },
+            )
+            .collect(),
+        next_cursor,
+        total,
+    })
+}
+
+fn filter_public_spells(u_id: i32, query_data: QueryPublicSpellsRequest) -> SpellQuery {
+    let mut query = spells::table.inner_join(users::table).into_boxed();
+    query = query.filter(published);
+    query = query.filter(deleted_at.is_null());
+    query = query.filter(user_id.ne(u_id));
+    query = filter_query(query, query_data.search(), query_data.filters);
+    if let Some(query_username) = query_data.username {
+        query = query.filter(username.eq(query_username))
+    }
+    query
+}
+
+/// Narrows down a query of spells by a search and the filters both queries of spells have, the
+/// filter tree included.
+fn filter_query(mut query: SpellQuery, search: SpellSearch, filters: SpellFilters) -> SpellQuery {
+    if let Some(query_search) = search.text {
+        query = query.filter(Matches::new(
+            search_vector,
+            spell_search_query(query_search),
+        ))
+    }
+    if let Some(query_name) = search.fuzzy_name {
+        query = query.filter(WordSimilarTo::new(query_name.into_sql::<Text>(), name))
+    } else if let Some(query_name) = filters.name {
         query = query.filter(name.ilike(format!("%{}%", query_name)))
     }
-    if let Some(query_level) = query_data.level {
-        query = query.filter(level.ilike(format!("%{}%", query_level)))
+    if let Some(query_level) = filters.level {
+        query = query.filter(level.eq(query_level))
+    }
+    if let Some(query_level_min) = filters.level_min {
+        query = query.filter(level.ge(query_level_min))
+    }
+    if let Some(query_level_max) = filters.level_max {
+        query = query.filter(level.le(query_level_max))
     }
-    if let Some(query_casting_time) = query_data.casting_time {
-        query = query.filter(casting_time.ilike(format!("%{}%", query_casting_time)))
+    if let Some(query_casting_time_unit) = filters.casting_time_unit {
+        query = query.filter(casting_time_unit.eq(query_casting_time_unit))
     }
-    if let Some(query_magic_school) = query_data.magic_school {
-        query = query.filter(magic_school.ilike(format!("%{}%", query_magic_school)))
+    if let Some(query_casting_time_quantity) = filters.casting_time_quantity {
+        query = query.filter(casting_time_quantity.eq(query_casting_time_quantity))
     }
-    if let Some(query_concentration) = query_data.concentration {
+    if let Some(query_magic_schools) = filters.magic_school {
+        let (standard_schools, homebrew_school_names) = split_schools(query_magic_schools);
+        query = query.filter(
+            magic_school
+                .eq_any(standard_schools)
+                .or(
+                    homebrew_school_id.eq_any(repositories::schools::school_ids_by_names(
+                        &homebrew_school_names,
+                    )),
+                ),
+        )
+    }
+    if let Some(query_concentration) = filters.concentration {
         query = query.filter(concentration.eq(query_concentration))
     }
-    if let Some(query_range) = query_data.range {
-        query = query.filter(range.ilike(format!("%{}%", query_range)))
+    if let Some(query_range_type) = filters.range_type {
+        query = query.filter(range_type.eq(query_range_type))
     }
-    if let Some(query_duration) = query_data.duration {
-        query = query.filter(duration.ilike(format!("%{}%", query_duration)))
+    if let Some(query_range_min_feet) = filters.range_min_feet {
+        query = query.filter(
+            range_type
+                .eq("Unlimited")
+                .or(range_feet.ge(query_range_min_feet)),
+        )
     }
-    query.load(conn)
+    if let Some(query_area_shape) = filters.area_shape {
+        query = query.filter(area_shape.eq(query_area_shape))
+    }
+    if let Some(query_has_area) = filters.has_area {
+        if query_has_area {
+            query = query.filter(area_shape.is_not_null())
+        } else {
+            query = query.filter(area_shape.is_null())
+        }
+    }
+    if let Some(query_duration_unit) = filters.duration_unit {
+        query = query.filter(duration_unit.eq(query_duration_unit))
+    }
+    if let Some(query_duration_min_minutes) = filters.duration_min_minutes {
+        query = query.filter(
+            duration_unit
+                .eq("UntilDispelled")
+                .or(duration_seconds.ge(query_duration_min_minutes * 60)),
+        )
+    }
+    if let Some(query_verbal) = filters.verbal {
+        query = query.filter(verbal.eq(query_verbal))
+    }
+    if let Some(query_somatic) = filters.somatic {
+        query = query.filter(somatic.eq(query_somatic))
+    }
+    if let Some(query_material) = filters.material {
+        query = query.filter(material.eq(query_material))
+    }
+    if let Some(query_costly_material) = filters.costly_material {
+        if query_costly_material {
+            query = query.filter(material_cost.is_not_null())
+        } else {
+            query = query.filter(material_cost.is_null())
+        }
+    }
+    if let Some(query_ritual) = filters.ritual {
+        query = query.filter(ritual.eq(query_ritual))
+    }
+    if let Some(query_classes) = filters.classes {
+        match filters.classes_match.unwrap_or_default() {
+            ClassMatch::Any => {
+                query = query.filter(
+                    spells::id.eq_any(
+                        spell_classes::table
+                            .select(spell_classes::spell_id)
+                            .filter(caster_class.eq_any(query_classes)),
+                    ),
+                )
+            }
+            ClassMatch::All => {
+                for query_class in query_classes {
+                    query = query.filter(
+                        spells::id.eq_any(
+                            spell_classes::table
+                                .select(spell_classes::spell_id)
+                                .filter(caster_class.eq(query_class)),
+                        ),
+                    )
+                }
+            }
+        }
+    }
+    if let Some(query_damage_type) = filters.damage_type {
+        query = query.filter(damage_types.contains(vec![query_damage_type]))
+    }
+    if let Some(query_healing) = filters.healing {
+        if query_healing {
+            query = query.filter(healing_dice.is_not_null())
+        } else {
+            query = query.filter(healing_dice.is_null())
+        }
+    }
+    if let Some(query_save_ability) = filters.save_ability {
+        query = query.filter(save_ability.eq(query_save_ability))
+    }
+    if let Some(query_attack_type) = filters.attack_type {
+        query = query.filter(attack_type.eq(query_attack_type))
+    }
+    if let Some(query_condition) = filters.condition {
+        query = query.filter(conditions.contains(vec![query_condition]))
+    }
+    if let Some(query_tags_any) = filters.tags_any {
+        query = query.filter(
+            spells::id.eq_any(
+                spell_tags::table
+                    .select(spell_tags::spell_id)
+                    .filter(tag.eq_any(query_tags_any)),
+            ),
+        )
+    }
+    if let Some(query_tags_all) = filters.tags_all {
+        for query_tag in query_tags_all {
+            query = query.filter(
+                spells::id.eq_any(
+                    spell_tags::table
+                        .select(spell_tags::spell_id)
+                        .filter(tag.eq(query_tag)),
+                ),
+            )
+        }
+    }
+    if let Some(query_source) = filters.source {
+        query = query.filter(source.ilike(format!("%{}%", query_source)))
+    }
+    if let Some(query_is_homebrew) = filters.is_homebrew {
+        query = query.filter(is_homebrew.eq(query_is_homebrew))
+    }
+    if let Some(query_license) = filters.license {
+        query = query.filter(license.eq(query_license))
+    }
+    if let Some(query_updated_since) = filters.updated_since {
+        query = query.filter(updated_at.ge(query_updated_since))
+    }
+    if let Some(query_filter) = filters.filter {
+        query = query.filter(filter_predicate(query_filter))
+    }
+    query
 }
 
-pub fn query_public_spells(
+/// Compiles a filter tree into a predicate. Conditions on empty fields are unknown, `not` counts
+/// them as false so negated conditions match spells without the field.
+fn filter_predicate(filter: Filter) -> FilterPredicate {
+    match filter {
+        Filter::And(filters) => filters
+            .into_iter()
+            .map(filter_predicate)
+            .reduce(|all, filter| Box::new(all.and(filter)))
+            .unwrap_or_else(|| Box::new(true.into_sql::<Nullable<Bool>>())),
+        Filter::Or(filters) => filters
+            .into_iter()
+            .map(filter_predicate)
+            .reduce(|any, filter| Box::new(any.or(filter)))
+            .unwrap_or_else(|| Box::new(false.into_sql::<Nullable<Bool>>())),
+        Filter::Not(filter) => Box::new(not(coalesce(filter_predicate(*filter), false)).nullable()),
+        Filter::Condition(FilterField::School, operator) => {
+            set_predicate(FilterField::School, operator)
+        }
+        Filter::Condition(field, operator) => match field.kind() {
+            FieldKind::Text | FieldKind::Keyword => text_predicate(|| text_column(field), operator),
+            FieldKind::Integer => integer_predicate(|| integer_column(field), operator),
+            FieldKind::Boolean => match operator {
+                FilterOperator::Eq(FilterValue::Boolean(value)) => {
+                    Box::new(boolean_column(field).eq(value))
+                }
+                _ => Box::new(false.into_sql::<Nullable<Bool>>()),
+            },
+            FieldKind::Time => time_predicate(|| time_column(field), operator),
+            FieldKind::List => set_predicate(field, operator),
+        },
+    }
+}
+
+// filters are validated beforehand, a condition that doesn't fit its field matches nothing
+fn text_predicate(
+    column: impl Fn() -> SpellExpression<Nullable<Text>>,
+    operator: FilterOperator,
+) -> FilterPredicate {
+    match operator {
+        FilterOperator::Eq(FilterValue::Text(value)) => Box::new(column().eq(value)),
+        FilterOperator::In(values) => Box::new(
+            column()
+                .eq_any(
+                    values
+                        .iter()
+                        .filter_map(|value| value.text().map(str::to_string))
+                        .collect::<Vec<String>>(),
+                )
+                .nullable(),
+        ),
+        FilterOperator::Contains(value) => {
+            Box::new(column().ilike(format!("%{}%", escape_like(&value))))
+        }
+        FilterOperator::StartsWith(value) => {
+            Box::new(column().ilike(format!("{}%", escape_like(&value))))
+        }
+        _ => Box::new(false.into_sql::<Nullable<Bool>>()),
+    }
+}
+
+fn integer_predicate(
+    column: impl Fn() -> SpellExpression<Nullable<Int4>>,
+    operator: FilterOperator,
+) -> FilterPredicate {
+    match operator {
+        FilterOperator::Eq(FilterValue::Integer(value)) => Box::new(column().eq(value)),
+        FilterOperator::In(values) => Box::new(
+            column()
+                .eq_any(
+                    values
+                        .iter()
+                        .filter_map(FilterValue::integer)
+                        .collect::<Vec<i32>>(),
+                )
+                .nullable(),
+        ),
+        FilterOperator::Range(range) => {
+            let mut predicate: FilterPredicate = Box::new(true.into_sql::<Nullable<Bool>>());
+            if let Some(min) = range.min.as_ref().and_then(FilterValue::integer) {
+                predicate = Box::new(predicate.and(column().ge(min)))
+            }
+            if let Some(max) = range.max.as_ref().and_then(FilterValue::integer) {
+                predicate = Box::new(predicate.and(column().le(max)))
+            }
+            predicate
+        }
+        _ => Box::new(false.into_sql::<Nullable<Bool>>()),
+    }
+}
+
+fn time_predicate(
+    column: impl Fn() -> SpellExpression<Timestamptz>,
+    operator: FilterOperator,
+) -> FilterPredicate {
+    match operator {
+        FilterOperator::Range(range) => {
+            let mut predicate: FilterPredicate = Box::new(true.into_sql::<Nullable<Bool>>());
+            if let Some(min) = range.min.as_ref().and_then(FilterValue::time) {
+                predicate = Box::new(predicate.and(column().ge(min)))
+            }
+            if let Some(max) = range.max.as_ref().and_then(FilterValue::time) {
+                predicate = Box::new(predicate.and(column().le(max)))
+            }
+            predicate
+        }
+        _ => Box::new(false.into_sql::<Nullable<Bool>>()),
+    }
+}
+
+/// Conditions on the fields that hold a set of names, and on the school, which is either one of
+/// the standard schools or the name of a homebrew school.
+fn set_predicate(field: FilterField, operator: FilterOperator) -> FilterPredicate {
+    let values = match operator {
+        FilterOperator::Eq(value) => vec![value],
+        FilterOperator::In(values) => values,
+        FilterOperator::Contains(value) => vec![FilterValue::Text(value)],
+        _ => Vec::new(),
+    };
+    let values: Vec<String> = values
+        .iter()
+        .filter_map(|value| value.text().map(str::to_string))
+        .collect();
+    match field {
+        FilterField::School => {
+            let (standard_schools, homebrew_school_names) =
+                split_schools(values.into_iter().map(SpellSchool::from).collect());
+            Box::new(
+                magic_school
+                    .eq_any(standard_schools)
+                    .or(
+                        homebrew_school_id.eq_any(repositories::schools::school_ids_by_names(
+                            &homebrew_school_names,
+                        )),
+                    )
+                    .nullable(),
+            )
+        }
+        FilterField::Classes => Box::new(
+            spells::id
+                .eq_any(
+                    spell_classes::table
+                        .select(spell_classes::spell_id)
+                        .filter(caster_class.eq_any(values)),
+                )
+                .nullable(),
+        ),
+        FilterField::Tags => Box::new(
+            spells::id
+                .eq_any(
+                    spell_tags::table
+                        .select(spell_tags::spell_id)
+                        .filter(tag.eq_any(values)),
+                )
+                .nullable(),
+        ),
+        FilterField::DamageTypes => Box::new(damage_types.overlaps_with(values).nullable()),
+        FilterField::Conditions => Box::new(conditions.overlaps_with(values).nullable()),
+        _ => Box::new(false.into_sql::<Nullable<Bool>>()),
+    }
+}
+
+fn text_column(field: FilterField) -> SpellExpression<Nullable<Text>> {
+    match field {
+        FilterField::Name => Box::new(name.nullable()),
+        FilterField::Description => Box::new(description.nullable()),
+        FilterField::CastingTimeUnit => Box::new(casting_time_unit.nullable()),
+        FilterField::RangeType => Box::new(range_type.nullable()),
+        FilterField::AreaShape => Box::new(area_shape),
+        FilterField::DurationUnit => Box::new(duration_unit.nullable()),
+        FilterField::SaveAbility => Box::new(save_ability),
+        FilterField::AttackType => Box::new(attack_type),
+        FilterField::Source => Box::new(source),
+        FilterField::License => Box::new(license),
+        FilterField::Author => Box::new(username.nullable()),
+        _ => Box::new(None::<String>.into_sql::<Nullable<Text>>()),
+    }
+}
+
+fn integer_column(field: FilterField) -> SpellExpression<Nullable<Int4>> {
+    match field {
+        FilterField::Level => Box::new(level.nullable()),
+        FilterField::CastingTimeQuantity => Box::new(casting_time_quantity.nullable()),
+        FilterField::RangeFeet => Box::new(range_feet),
+        FilterField::DurationSeconds => Box::new(duration_seconds),
+        FilterField::MaterialCost => Box::new(material_cost),
+        _ => Box::new(None::<i32>.into_sql::<Nullable<Int4>>()),
+    }
+}
+
+fn boolean_column(field: FilterField) -> SpellExpression<Nullable<Bool>> {
+    match field {
+        FilterField::Concentration => Box::new(concentration.nullable()),
+        FilterField::Verbal => Box::new(verbal.nullable()),
+        FilterField::Somatic => Box::new(somatic.nullable()),
+        FilterField::Material => Box::new(material.nullable()),
+        FilterField::Ritual => Box::new(ritual.nullable()),
+        FilterField::IsHomebrew => Box::new(is_homebrew.nullable()),
+        _ => Box::new(None::<bool>.into_sql::<Nullable<Bool>>()),
+    }
+}
+
+fn time_column(field: FilterField) -> SpellExpression<Timestamptz> {
+    match field {
+        FilterField::CreatedAt => Box::new(created_at),
+        _ => Box::new(updated_at),
+    }
+}
+
+/// A spell with its author, the name of its school, the number of copies made of it and its
+/// relevance to the search, which are all it can be sorted by, and its search snippet.
+type SortedSpell = (Spell, User, String, i64, f32, Option<String>);
+
+/// Loads a page of spells with their authors, the total is left to the caller. The spells are
+/// sorted by the sort keys and then by their nanoid, so every spell has a fixed position to
+/// continue from no matter how many share the same values.
+fn load_page(
     conn: &mut PgConnection,
-    u_id: i32,
-    query_data: QueryPublicSpellsRequest,
-) -> Result<Vec<(Spell, User)>, diesel::result::Error> {
-    let mut query = spells::table.inner_join(users::table).into_boxed();
-    query = query.filter(published);
-    query = query.filter(user_id.ne(u_id));
-    if let Some(query_name) = query_data.name {
-        query = query.filter(name.ilike(format!("%{}%", query_name)))
+    mut query: SpellQuery,
+    sort: &[SortKey],
+    search: &SpellSearch,
+    page: &PageRequest,
+) -> Result<Page<(Spell, User, Option<String>)>, diesel::result::Error> {
+    let school_name = || spell_school_name(magic_school, homebrew_school_id);
+    let copy_count = || spell_copy_count(spells::id);
+    for (sort_by, sort_order) in sort {
+        query = match (sort_by, sort_order) {
+            (SortBy::Name, SortOrder::Asc) => query.then_order_by(name.asc()),
+            (SortBy::Name, SortOrder::Desc) => query.then_order_by(name.desc()),
+            (SortBy::Level, SortOrder::Asc) => query.then_order_by(level.asc()),
+            (SortBy::Level, SortOrder::Desc) => query.then_order_by(level.desc()),
+            (SortBy::School, SortOrder::Asc) => query.then_order_by(school_name().asc()),
+            (SortBy::School, SortOrder::Desc) => query.then_order_by(school_name().desc()),
+            (SortBy::CreatedAt, SortOrder::Asc) => query.then_order_by(created_at.asc()),
+            (SortBy::CreatedAt, SortOrder::Desc) => query.then_order_by(created_at.desc()),
+            (SortBy::UpdatedAt, SortOrder::Asc) => query.then_order_by(updated_at.asc()),
+            (SortBy::UpdatedAt, SortOrder::Desc) => query.then_order_by(updated_at.desc()),
+            (SortBy::Author, SortOrder::Asc) => query.then_order_by(username.asc()),
+            (SortBy::Author, SortOrder::Desc) => query.then_order_by(username.desc()),
+            (SortBy::Popularity, SortOrder::Asc) => query.then_order_by(copy_count().asc()),
+            (SortBy::Popularity, SortOrder::Desc) => query.then_order_by(copy_count().desc()),
+            (SortBy::Relevance, SortOrder::Asc) => query.then_order_by(search_rank(search).asc()),
+            (SortBy::Relevance, SortOrder::Desc) => query.then_order_by(search_rank(search).desc()),
+        }
     }
-    if let Some(query_level) = query_data.level {
-        query = query.filter(level.ilike(format!("%{}%", query_level)))
+    query = query.then_order_by(nanoid.asc());
+    if let Some(cursor) = page.cursor() {
+        query = query.filter(after_cursor(sort, search, &cursor));
     }
-    if let Some(query_casting_time) = query_data.casting_time {
-        query = query.filter(casting_time.ilike(format!("%{}%", query_casting_time)))
+
+    // one spell more than asked for tells if there is a next page
+    let limit = page.limit();
+    let mut spells = query
+        .select((
+            Spell::as_select(),
+            User::as_select(),
+            school_name(),
+            copy_count(),
+            search_rank(search),
+            search_snippet(search),
+        ))
+        .limit(limit + 1)
+        .load::<SortedSpell>(conn)?;
+    let next_cursor = if spells.len() as i64 > limit {
+        spells.truncate(limit as usize);
+        spells.last().map(|spell| Cursor {
+            values: sort
+                .iter()
+                .map(|(sort_by, _)| sort_value(spell, *sort_by))
+                .collect(),
+            nanoid: spell.0.nanoid.to_string(),
+        })
+    } else {
+        None
+    };
+    Ok(Page {
+        items: spells
+            .into_iter()
+            .map(|(spell, user, _, _, _, snippet)| (spell, user, snippet))
+            .collect(),
+        next_cursor,
+        total: None,
+    })
+}
+
+/// Matches the spells after the cursor: those beyond it in the first sort key, or equal in it
+/// and after it in the remaining keys, down to the nanoid.
+fn after_cursor(sort: &[SortKey], search: &SpellSearch, cursor: &Cursor) -> SpellPredicate {
+    let mut after: SpellPredicate = Box::new(nanoid.gt(cursor.nanoid.to_string()));
+    for ((sort_by, sort_order), value) in sort.iter().zip(&cursor.values).rev() {
+        after = Box::new(
+            beyond_sort_value(*sort_by, *sort_order, search, value)
+                .or(equal_to_sort_value(*sort_by, search, value).and(after)),
+        );
     }
-    if let Some(query_magic_school) = query_data.magic_school {
-        query = query.filter(magic_school.ilike(format!("%{}%", query_magic_school)))
+    after
+}
+
+/// How well a spell matches the search: its text search rank, or how similar its name is to
+/// a fuzzy name. Without a search all spells are equally relevant.
+fn search_rank(search: &SpellSearch) -> SpellExpression<Float4> {
+    match (&search.text, &search.fuzzy_name) {
+        (Some(text), _) => Box::new(ts_rank(search_vector, spell_search_query(text.to_string()))),
+        (None, Some(fuzzy_name)) => Box::new(word_similarity(fuzzy_name.to_string(), name)),
+        (None, None) => Box::new(0.0.into_sql::<Float4>()),
     }
-    if let Some(query_concentration) = query_data.concentration {
-        query = query.filter(concentration.eq(query_concentration))
+}
+
+/// The parts of the description of a spell that match the search, with the matched words
+/// wrapped in `<mark>` tags.
+fn search_snippet(search: &SpellSearch) -> SpellExpression<Nullable<Text>> {
+    match &search.text {
+        Some(text) => Box::new(
+            spell_search_headline(description, spell_search_query(text.to_string())).nullable(),
+        ),
+        None => Box::new(None::<String>.into_sql::<Nullable<Text>>()),
     }
-    if let Some(query_range) = query_data.range {
-        query = query.filter(range.ilike(format!("%{}%", query_range)))
+}
+
+// cursors are checked to fit the sort keys beforehand, a value of another key matches nothing
+fn beyond_sort_value(
+    sort_by: SortBy,
+    sort_order: SortOrder,
+    search: &SpellSearch,
+    value: &SortValue,
+) -> SpellPredicate {
+    let school_name = || spell_school_name(magic_school, homebrew_school_id);
+    let copy_count = || spell_copy_count(spells::id);
+    match (sort_by, sort_order, value) {
+        (SortBy::Name, SortOrder::Asc, SortValue::Text(v)) => Box::new(name.gt(v.to_string())),
+        (SortBy::Name, SortOrder::Desc, SortValue::Text(v)) => Box::new(name.lt(v.to_string())),
+        (SortBy::Level, SortOrder::Asc, SortValue::Integer(v)) => Box::new(level.gt(*v as i32)),
+        (SortBy::Level, SortOrder::Desc, SortValue::Integer(v)) => Box::new(level.lt(*v as i32)),
+        (SortBy::School, SortOrder::Asc, SortValue::Text(v)) => {
+            Box::new(school_name().gt(v.to_string()))
+        }
+        (SortBy::School, SortOrder::Desc, SortValue::Text(v)) => {
+            Box::new(school_name().lt(v.to_string()))
+        }
+        (SortBy::CreatedAt, SortOrder::Asc, SortValue::Time(v)) => Box::new(created_at.gt(*v)),
+        (SortBy::CreatedAt, SortOrder::Desc, SortValue::Time(v)) => Box::new(created_at.lt(*v)),
+        (SortBy::UpdatedAt, SortOrder::Asc, SortValue::Time(v)) => Box::new(updated_at.gt(*v)),
+        (SortBy::UpdatedAt, SortOrder::Desc, SortValue::Time(v)) => Box::new(updated_at.lt(*v)),
+        (SortBy::Author, SortOrder::Asc, SortValue::Text(v)) => {
+            Box::new(username.gt(v.to_string()))
+        }
+        (SortBy::Author, SortOrder::Desc, SortValue::Text(v)) => {
+            Box::new(username.lt(v.to_string()))
+        }
+        (SortBy::Popularity, SortOrder::Asc, SortValue::Integer(v)) => {
+            Box::new(copy_count().gt(*v))
+        }
+        (SortBy::Popularity, SortOrder::Desc, SortValue::Integer(v)) => {
+            Box::new(copy_count().lt(*v))
+        }
+        (SortBy::Relevance, SortOrder::Asc, SortValue::Real(v)) => {
+            Box::new(search_rank(search).gt(*v))
+        }
+        (SortBy::Relevance, SortOrder::Desc, SortValue::Real(v)) => {
+            Box::new(search_rank(search).lt(*v))
+        }
+        _ => Box::new(false.into_sql::<Bool>()),
     }
-    if let Some(query_duration) = query_data.duration {
-        query = query.filter(duration.ilike(format!("%{}%", query_duration)))
+}
+
+fn equal_to_sort_value(sort_by: SortBy, search: &SpellSearch, value: &SortValue) -> SpellPredicate {
+    match (sort_by, value) {
+        (SortBy::Name, SortValue::Text(v)) => Box::new(name.eq(v.to_string())),
+        (SortBy::Level, SortValue::Integer(v)) => Box::new(level.eq(*v as i32)),
+        (SortBy::School, SortValue::Text(v)) => {
+            Box::new(spell_school_name(magic_school, homebrew_school_id).eq(v.to_string()))
+        }
+        (SortBy::CreatedAt, SortValue::Time(v)) => Box::new(created_at.eq(*v)),
+        (SortBy::UpdatedAt, SortValue::Time(v)) => Box::new(updated_at.eq(*v)),
+        (SortBy::Author, SortValue::Text(v)) => Box::new(username.eq(v.to_string())),
+        (SortBy::Popularity, SortValue::Integer(v)) => {
+            Box::new(spell_copy_count(spells::id).eq(*v))
+        }
+        (SortBy::Relevance, SortValue::Real(v)) => Box::new(search_rank(search).eq(*v)),
+        _ => Box::new(false.into_sql::<Bool>()),
     }
-    if let Some(query_username) = query_data.username {
-        query = query.filter(username.eq(query_username))
+}
+
+fn sort_value(spell: &SortedSpell, sort_by: SortBy) -> SortValue {
+    let (spell, user, school_name, copy_count, rank, _) = spell;
+    match sort_by {
+        SortBy::Name => SortValue::Text(spell.name.to_string()),
+        SortBy::Level => SortValue::Integer(spell.level.into()),
+        SortBy::School => SortValue::Text(school_name.to_string()),
+        SortBy::CreatedAt => SortValue::Time(spell.created_at),
+        SortBy::UpdatedAt => SortValue::Time(spell.updated_at),
+        SortBy::Author => SortValue::Text(user.username.to_string()),
+        SortBy::Popularity => SortValue::Integer(*copy_count),
+        SortBy::Relevance => SortValue::Real(*rank),
     }
-    query.load(conn)
 }
 
 pub fn get_public_spell(
@@ -172,6 +1283,7 @@ pub fn get_public_spell(
         .select(Spell::as_select())
         .filter(published)
         .filter(nanoid.eq(n_id))
+        .filter(deleted_at.is_null())
         .first(conn)
 }
 
@@ -184,6 +1296,7 @@ pub fn is_published(
         .select(Spell::as_select())
         .filter(user_id.eq(u_id))
         .filter(nanoid.eq(n_id))
+        .filter(deleted_at.is_null())
         .get_result(conn)
         .map(|s| s.published)
 }
@@ -0,0 +1,310 @@
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use strum::{Display, IntoStaticStr};
+
+use crate::errors::FilterValidationError;
+
+/// A tree of conditions on the fields of spells. Conditions are combined with `and`, `or` and
+/// `not` nodes, which nest as deep as needed.
+#[derive(Deserialize, Clone)]
+#[serde(try_from = "FilterNode")]
+pub enum Filter {
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+    Not(Box<Filter>),
+    Condition(FilterField, FilterOperator),
+}
+
+/// A node of a filter as it is written, either `and`, `or` or `not`, or a `field` with exactly
+/// one operator.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FilterNode {
+    and: Option<Vec<Filter>>,
+    or: Option<Vec<Filter>>,
+    not: Option<Box<Filter>>,
+    field: Option<FilterField>,
+    eq: Option<FilterValue>,
+    #[serde(rename = "in")]
+    any_of: Option<Vec<FilterValue>>,
+    contains: Option<String>,
+    starts_with: Option<String>,
+    range: Option<FilterRange>,
+}
+
+impl TryFrom<FilterNode> for Filter {
+    type Error = FilterValidationError;
+
+    fn try_from(node: FilterNode) -> Result<Self, Self::Error> {
+        let mut operators = [
+            node.eq.map(FilterOperator::Eq),
+            node.any_of.map(FilterOperator::In),
+            node.contains.map(FilterOperator::Contains),
+            node.starts_with.map(FilterOperator::StartsWith),
+            node.range.map(FilterOperator::Range),
+        ]
+        .into_iter()
+        .flatten();
+        match (
+            node.and,
+            node.or,
+            node.not,
+            node.field,
+            operators.next(),
+            operators.next(),
+        ) {
+            (Some(filters), None, None, None, None, None) => Ok(Filter::And(filters)),
+            (None, Some(filters), None, None, None, None) => Ok(Filter::Or(filters)),
+            (None, None, Some(filter), None, None, None) => Ok(Filter::Not(filter)),
+            (None, None, None, Some(field), Some(operator), None) => {
+                Ok(Filter::Condition(field, operator))
+            }
+            _ => Err(FilterValidationError::InvalidNode),
+        }
+    }
+}
+
+#[derive(Deserialize, Display, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum FilterField {
+    Name,
+    Description,
+    Level,
+    School,
+    CastingTimeUnit,
+    CastingTimeQuantity,
+    Concentration,
+    RangeType,
+    RangeFeet,
+    AreaShape,
+    DurationUnit,
+    DurationSeconds,
+    Verbal,
+    Somatic,
+    Material,
+    MaterialCost,
+    Ritual,
+    Classes,
+    DamageTypes,
+    SaveAbility,
+    AttackType,
+    Conditions,
+    Tags,
+    Source,
+    IsHomebrew,
+    License,
+    Author,
+    CreatedAt,
+    UpdatedAt,
+}
+
+/// What a field holds, which decides the operators it can be filtered with.
+#[derive(Clone, Copy)]
+pub enum FieldKind {
+    /// Free text, compared as a whole or case insensitively in part.
+    Text,
+    /// One of a fixed set of names, compared as a whole.
+    Keyword,
+    Integer,
+    Boolean,
+    /// A set of names, which contains a name or any of several.
+    List,
+    Time,
+}
+
+impl FilterField {
+    pub fn kind(self) -> FieldKind {
+        match self {
+            FilterField::Name
+            | FilterField::Description
+            | FilterField::Source
+            | FilterField::Author => FieldKind::Text,
+            FilterField::School
+            | FilterField::CastingTimeUnit
+            | FilterField::RangeType
+            | FilterField::AreaShape
+            | FilterField::DurationUnit
+            | FilterField::SaveAbility
+            | FilterField::AttackType
+            | FilterField::License => FieldKind::Keyword,
+            FilterField::Level
+            | FilterField::CastingTimeQuantity
+            | FilterField::RangeFeet
+            | FilterField::DurationSeconds
+            | FilterField::MaterialCost => FieldKind::Integer,
+            FilterField::Concentration
+            | FilterField::Verbal
+            | FilterField::Somatic
+            | FilterField::Material
+            | FilterField::Ritual
+            | FilterField::IsHomebrew => FieldKind::Boolean,
+            FilterField::Classes
+            | FilterField::DamageTypes
+            | FilterField::Conditions
+            | FilterField::Tags => FieldKind::List,
+            FilterField::CreatedAt | FilterField::UpdatedAt => FieldKind::Time,
+        }
+    }
+}
+
+#[derive(IntoStaticStr, Clone)]
+#[strum(serialize_all = "snake_case")]
+pub enum FilterOperator {
+    Eq(FilterValue),
+    In(Vec<FilterValue>),
+    Contains(String),
+    StartsWith(String),
+    Range(FilterRange),
+}
+
+/// Bounds of a range, both inclusive, at least one of them is given.
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct FilterRange {
+    pub min: Option<FilterValue>,
+    pub max: Option<FilterValue>,
+}
+
+/// Times are written as RFC 3339 text.
+#[derive(Deserialize, Clone)]
+#[serde(untagged)]
+pub enum FilterValue {
+    Boolean(bool),
+    Integer(i32),
+    Text(String),
+}
+
+impl FilterValue {
+    pub fn boolean(&self) -> Option<bool> {
+        match self {
+            FilterValue::Boolean(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn integer(&self) -> Option<i32> {
+        match self {
+            FilterValue::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn text(&self) -> Option<&str> {
+        match self {
+            FilterValue::Text(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn time(&self) -> Option<DateTime<Utc>> {
+        self.text()
+            .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+            .map(|value| value.with_timezone(&Utc))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn parse(filter: serde_json::Value) -> Result<Filter, serde_json::Error> {
+        serde_json::from_value(filter)
+    }
+
+    #[test]
+    fn parses_conditions() {
+        let filter = parse(json!({ "field": "level", "eq": 3 })).unwrap();
+        assert!(matches!(
+            filter,
+            Filter::Condition(
+                FilterField::Level,
+                FilterOperator::Eq(FilterValue::Integer(3))
+            )
+        ));
+
+        let filter = parse(json!({ "field": "classes", "in": ["Wizard", "Sorcerer"] })).unwrap();
+        assert!(matches!(
+            filter,
+            Filter::Condition(FilterField::Classes, FilterOperator::In(values)) if values.len() == 2
+        ));
+
+        let filter =
+            parse(json!({ "field": "created_at", "range": { "min": "2026-10-19T09:00:00Z" } }))
+                .unwrap();
+        assert!(matches!(
+            filter,
+            Filter::Condition(
+                FilterField::CreatedAt,
+                FilterOperator::Range(FilterRange {
+                    min: Some(_),
+                    max: None
+                })
+            )
+        ));
+    }
+
+    #[test]
+    fn parses_nested_nodes() {
+        let filter = parse(json!({
+            "and": [
+                { "field": "name", "starts_with": "Fire" },
+                { "not": { "field": "concentration", "eq": true } },
+                { "or": [
+                    { "field": "description", "contains": "fire" },
+                    { "field": "damage_types", "contains": "Fire" },
+                ] },
+            ]
+        }))
+        .unwrap();
+
+        let Filter::And(filters) = filter else {
+            panic!("expected an and node");
+        };
+        assert!(matches!(
+            filters.as_slice(),
+            [
+                Filter::Condition(FilterField::Name, FilterOperator::StartsWith(_)),
+                Filter::Not(_),
+                Filter::Or(or),
+            ] if or.len() == 2
+        ));
+    }
+
+    #[test]
+    fn rejects_nodes_without_exactly_one_kind() {
+        let invalid_node = FilterValidationError::InvalidNode.to_string();
+        for filter in [
+            json!({}),
+            json!({ "field": "level" }),
+            json!({ "eq": 3 }),
+            json!({ "field": "level", "eq": 3, "in": [3] }),
+            json!({ "and": [], "or": [] }),
+            json!({ "not": { "field": "ritual", "eq": true }, "field": "level", "eq": 3 }),
+        ] {
+            let error = parse(filter.clone()).err().unwrap();
+            assert!(error.to_string().contains(&invalid_node), "{filter}");
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_fields_and_keys() {
+        assert!(parse(json!({ "field": "colour", "eq": "red" })).is_err());
+        assert!(parse(json!({ "field": "level", "equals": 3 })).is_err());
+        assert!(parse(json!({ "field": "level", "range": { "min": 1, "step": 2 } })).is_err());
+    }
+
+    #[test]
+    fn reads_values_as_their_kind() {
+        assert_eq!(FilterValue::Boolean(true).boolean(), Some(true));
+        assert_eq!(FilterValue::Integer(3).integer(), Some(3));
+        assert_eq!(FilterValue::Text("Fire".to_string()).text(), Some("Fire"));
+        assert_eq!(FilterValue::Integer(3).text(), None);
+        assert!(FilterValue::Text("2026-10-19T09:00:00+02:00".to_string())
+            .time()
+            .is_some_and(|time| time.to_rfc3339() == "2026-10-19T07:00:00+00:00"));
+        assert!(FilterValue::Text("yesterday".to_string()).time().is_none());
+    }
+}
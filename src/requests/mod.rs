@@ -1,2 +1,6 @@
+pub mod filters;
+pub mod pages;
+pub mod revisions;
+pub mod schools;
 pub mod spells;
 pub mod users;
@@ -0,0 +1,184 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::requests::spells::{SortBy, SortKey};
+
+pub const DEFAULT_PAGE_SIZE: i64 = 50;
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+#[derive(Deserialize)]
+pub struct PageRequest {
+    pub limit: Option<i64>,
+    pub cursor: Option<String>,
+    #[serde(default)]
+    pub total: bool,
+}
+
+impl PageRequest {
+    /// Larger limits are capped at the maximum page size instead of being rejected. Bare lists
+    /// are pages just the same, the `Accept` header only decides how a page is written.
+    pub fn limit(&self) -> i64 {
+        self.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE)
+    }
+
+    pub fn cursor(&self) -> Option<Cursor> {
+        self.cursor.as_deref().and_then(Cursor::decode)
+    }
+}
+
+/// The position after the last spell of a page: its values of the sort keys and its nanoid,
+/// which breaks ties. Clients only ever see it as an opaque string.
+#[derive(Serialize, Deserialize)]
+pub struct Cursor {
+    pub values: Vec<SortValue>,
+    pub nanoid: String,
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    /// Whether the cursor was made for the same sort keys, cursors of another sort order point
+    /// to no position in this one.
+    pub fn fits(&self, sort: &[SortKey]) -> bool {
+        self.values.len() == sort.len()
+            && self
+                .values
+                .iter()
+                .zip(sort)
+                .all(|(value, (sort_by, _))| value.fits(*sort_by))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum SortValue {
+    Text(String),
+    Integer(i64),
+    Real(f32),
+    Time(DateTime<Utc>),
+}
+
+impl SortValue {
+    fn fits(&self, sort_by: SortBy) -> bool {
+        matches!(
+            (self, sort_by),
+            (
+                SortValue::Text(_),
+                SortBy::Name | SortBy::School | SortBy::Author
+            ) | (SortValue::Integer(_), SortBy::Level | SortBy::Popularity)
+                | (SortValue::Real(_), SortBy::Relevance)
+                | (SortValue::Time(_), SortBy::CreatedAt | SortBy::UpdatedAt)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::requests::spells::SortOrder;
+
+    fn cursor(values: Vec<SortValue>) -> Cursor {
+        Cursor {
+            values,
+            nanoid: "V1StGXR8_Z5jdHi6B-myT".to_string(),
+        }
+    }
+
+    fn page(limit: Option<i64>) -> PageRequest {
+        PageRequest {
+            limit,
+            cursor: None,
+            total: false,
+        }
+    }
+
+    #[test]
+    fn decodes_encoded_cursors() {
+        let time = Utc::now();
+        let encoded = cursor(vec![
+            SortValue::Text("Fireball".to_string()),
+            SortValue::Integer(3),
+            SortValue::Real(0.5),
+            SortValue::Time(time),
+        ])
+        .encode();
+        let decoded = Cursor::decode(&encoded).unwrap();
+
+        assert_eq!(decoded.nanoid, "V1StGXR8_Z5jdHi6B-myT");
+        assert!(matches!(
+            decoded.values.as_slice(),
+            [
+                SortValue::Text(text),
+                SortValue::Integer(3),
+                SortValue::Real(real),
+                SortValue::Time(decoded_time),
+            ] if text == "Fireball" && *real == 0.5 && *decoded_time == time
+        ));
+    }
+
+    #[test]
+    fn encodes_cursors_safe_for_urls() {
+        let encoded = cursor(vec![SortValue::Text("?&/+= ".repeat(10))]).encode();
+        assert!(encoded
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+    }
+
+    #[test]
+    fn does_not_decode_other_strings() {
+        assert!(Cursor::decode("").is_none());
+        assert!(Cursor::decode("not a cursor").is_none());
+        assert!(Cursor::decode(&URL_SAFE_NO_PAD.encode(b"{\"values\":[]}")).is_none());
+    }
+
+    #[test]
+    fn cursors_fit_the_sort_keys_they_were_made_for() {
+        let cursor = cursor(vec![
+            SortValue::Integer(3),
+            SortValue::Text("Fireball".to_string()),
+        ]);
+        assert!(cursor.fits(&[
+            (SortBy::Level, SortOrder::Desc),
+            (SortBy::Name, SortOrder::Asc)
+        ]));
+        assert!(cursor.fits(&[
+            (SortBy::Popularity, SortOrder::Asc),
+            (SortBy::Author, SortOrder::Asc)
+        ]));
+    }
+
+    #[test]
+    fn cursors_do_not_fit_other_sort_keys() {
+        let cursor = cursor(vec![SortValue::Integer(3)]);
+        assert!(!cursor.fits(&[(SortBy::Name, SortOrder::Asc)]));
+        assert!(!cursor.fits(&[(SortBy::Relevance, SortOrder::Desc)]));
+        assert!(!cursor.fits(&[]));
+        assert!(!cursor.fits(&[
+            (SortBy::Level, SortOrder::Asc),
+            (SortBy::Name, SortOrder::Asc)
+        ]));
+    }
+
+    #[test]
+    fn sort_values_fit_their_keys() {
+        assert!(SortValue::Text(String::new()).fits(SortBy::School));
+        assert!(SortValue::Real(1.0).fits(SortBy::Relevance));
+        assert!(SortValue::Time(Utc::now()).fits(SortBy::UpdatedAt));
+        assert!(!SortValue::Time(Utc::now()).fits(SortBy::Level));
+        assert!(!SortValue::Real(1.0).fits(SortBy::Popularity));
+    }
+
+    #[test]
+    fn limits_pages() {
+        assert_eq!(page(None).limit(), DEFAULT_PAGE_SIZE);
+        assert_eq!(page(Some(10)).limit(), 10);
+        assert_eq!(page(Some(1000)).limit(), MAX_PAGE_SIZE);
+    }
+}
@@ -0,0 +1,7 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct RevisionDiffRequest {
+    pub from: i32,
+    pub to: i32,
+}
@@ -0,0 +1,16 @@
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct CreateHomebrewSchoolRequest {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub shared: bool,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateHomebrewSchoolRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub shared: Option<bool>,
+}
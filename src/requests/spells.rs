@@ -1,55 +1,563 @@
-use serde::Deserialize;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer};
+
+use strum::{Display, EnumString, VariantNames};
+
+use crate::{enums::MagicSchool, errors::SortValidationError, requests::filters::Filter};
+
+/// The school of a spell, either one of the standard schools or the name of a homebrew school.
+#[derive(Deserialize, Clone)]
+#[serde(from = "String")]
+pub enum SpellSchool {
+    Standard(MagicSchool),
+    Homebrew(String),
+}
+
+impl SpellSchool {
+    pub fn standard(&self) -> Option<MagicSchool> {
+        match self {
+            SpellSchool::Standard(magic_school) => Some(*magic_school),
+            SpellSchool::Homebrew(_) => None,
+        }
+    }
+}
+
+impl From<String> for SpellSchool {
+    fn from(school: String) -> Self {
+        match MagicSchool::from_str(school.trim()) {
+            Ok(magic_school) => SpellSchool::Standard(magic_school),
+            Err(_) => SpellSchool::Homebrew(school.trim().to_string()),
+        }
+    }
+}
 
 #[derive(Deserialize)]
 pub struct CreateSpellRequest {
     pub name: String,
-    pub level: String,
-    pub casting_time: String,
-    pub magic_school: String,
+    pub level: i32,
+    pub casting_time_unit: String,
+    pub casting_time_quantity: Option<i32>,
+    pub reaction_trigger: Option<String>,
+    pub magic_school: SpellSchool,
     pub concentration: bool,
-    pub range: String,
-    pub duration: String,
+    pub range_type: String,
+    pub range_distance: Option<i32>,
+    pub range_unit: Option<String>,
+    pub area_shape: Option<String>,
+    pub area_size: Option<i32>,
+    pub duration_unit: String,
+    pub duration_quantity: Option<i32>,
+    #[serde(default)]
+    pub duration_up_to: bool,
     pub description: String,
+    pub verbal: bool,
+    pub somatic: bool,
+    pub material: bool,
+    pub material_description: Option<String>,
+    pub material_cost: Option<i32>,
+    #[serde(default)]
+    pub material_consumed: bool,
+    pub ritual: bool,
+    #[serde(default)]
+    pub classes: Vec<String>,
+    pub damage_dice: Option<String>,
+    pub healing_dice: Option<String>,
+    pub higher_levels: Option<String>,
+    pub higher_level_dice: Option<String>,
+    #[serde(default)]
+    pub cantrip_scaling: bool,
+    #[serde(default)]
+    pub damage_types: Vec<String>,
+    pub save_ability: Option<String>,
+    pub attack_type: Option<String>,
+    #[serde(default)]
+    pub conditions: Vec<String>,
+    pub source: Option<String>,
+    pub page: Option<i32>,
+    #[serde(default)]
+    pub is_homebrew: bool,
+    pub license: Option<String>,
+    pub license_label: Option<String>,
+}
+
+impl CreateSpellRequest {
+    /// Distances are in feet unless the request says otherwise.
+    pub fn range_unit(&self) -> Option<&str> {
+        match (&self.range_unit, self.range_distance) {
+            (Some(range_unit), _) => Some(range_unit),
+            (None, Some(_)) => Some("Feet"),
+            (None, None) => None,
+        }
+    }
 }
 
+/// Replaces a spell as a whole, so it has to hold everything a new spell does.
+pub type UpdateSpellRequest = CreateSpellRequest;
+
+/// A JSON merge patch (RFC 7396) of a spell, fields that are left out keep their value and
+/// fields that are `null` are cleared.
 #[derive(Deserialize)]
-pub struct UpdatedSpellData {
-    pub name: Option<String>,
-    pub level: Option<String>,
-    pub casting_time: Option<String>,
-    pub magic_school: Option<String>,
-    pub concentration: Option<bool>,
-    pub range: Option<String>,
-    pub duration: Option<String>,
-    pub description: Option<String>,
+#[serde(transparent)]
+pub struct PatchSpellRequest(serde_json::Value);
+
+impl PatchSpellRequest {
+    /// Applies the patch to the current fields of a spell, which gives the spell that replaces it.
+    /// Fields the spell doesn't have are rejected, they would be dropped without notice.
+    pub fn apply(
+        &self,
+        mut spell: serde_json::Value,
+    ) -> Result<UpdateSpellRequest, serde_json::Error> {
+        if let (serde_json::Value::Object(patch), serde_json::Value::Object(spell)) =
+            (&self.0, &spell)
+        {
+            if let Some(field) = patch.keys().find(|field| !spell.contains_key(*field)) {
+                return Err(serde::de::Error::custom(format!(
+                    "unknown field \"{}\"",
+                    field
+                )));
+            }
+        }
+        merge_patch(&mut spell, &self.0);
+        serde_json::from_value(spell)
+    }
+}
+
+fn merge_patch(target: &mut serde_json::Value, patch: &serde_json::Value) {
+    let serde_json::Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = serde_json::Value::Object(serde_json::Map::new());
+    }
+    if let serde_json::Value::Object(target) = target {
+        for (field, value) in patch {
+            if value.is_null() {
+                target.remove(field);
+            } else {
+                merge_patch(
+                    target.entry(field).or_insert(serde_json::Value::Null),
+                    value,
+                );
+            }
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SpellEffectRequest {
+    pub slot_level: Option<i32>,
+    pub character_level: Option<i32>,
+}
+
+#[derive(Deserialize)]
+pub struct SpellTagsRequest {
+    pub tags: Vec<String>,
+}
+
+impl SpellTagsRequest {
+    pub fn trimmed_tags(&self) -> Vec<&str> {
+        self.tags.iter().map(|tag| tag.trim()).collect()
+    }
 }
 
-pub type UpdateSpellRequest = UpdatedSpellData;
+#[derive(Deserialize)]
+pub struct TagAutocompleteRequest {
+    pub prefix: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct SpellSuggestionRequest {
+    pub prefix: String,
+}
 
 #[derive(Deserialize)]
 pub struct GetPublicSpellRequest {
     pub keyword: String,
 }
 
+/// Whether a spell has to be on the spell list of any or all of the queried classes.
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ClassMatch {
+    #[default]
+    Any,
+    All,
+}
+
+/// What spells are sorted by. Only public spells have different authors and can be popular, and
+/// only searches rank spells by relevance.
+#[derive(EnumString, VariantNames, Display, Clone, Copy, Debug)]
+#[strum(serialize_all = "snake_case")]
+pub enum SortBy {
+    Name,
+    Level,
+    School,
+    CreatedAt,
+    UpdatedAt,
+    Author,
+    Popularity,
+    Relevance,
+}
+
+#[derive(Clone, Copy)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+/// One key spells are sorted by, together with its direction.
+pub type SortKey = (SortBy, SortOrder);
+
+/// Reads sort keys like `-level,name`, most significant first. A leading `-` sorts by a key in
+/// descending order. Searches are sorted by relevance unless sorted otherwise.
+fn sort_keys(sort: Option<&str>, searching: bool) -> Result<Vec<SortKey>, SortValidationError> {
+    let sort = match sort {
+        Some(sort) => parse_sort(sort)?,
+        None => Vec::new(),
+    };
+    if !searching
+        && sort
+            .iter()
+            .any(|(sort_by, _)| matches!(sort_by, SortBy::Relevance))
+    {
+        return Err(SortValidationError::RelevanceWithoutSearch);
+    }
+    if searching && sort.is_empty() {
+        return Ok(vec![(SortBy::Relevance, SortOrder::Desc)]);
+    }
+    Ok(sort)
+}
+
+fn parse_sort(sort: &str) -> Result<Vec<SortKey>, SortValidationError> {
+    sort.split(',')
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(|key| {
+            let (key, sort_order) = match key.strip_prefix('-') {
+                Some(key) => (key, SortOrder::Desc),
+                None => (key, SortOrder::Asc),
+            };
+            match SortBy::from_str(key) {
+                Ok(sort_by) => Ok((sort_by, sort_order)),
+                Err(_) => Err(SortValidationError::InvalidKey(key.to_string())),
+            }
+        })
+        .collect()
+}
+
+/// Your own spells are all by you and never copied.
+fn private_sort_keys(sort: Vec<SortKey>) -> Result<Vec<SortKey>, SortValidationError> {
+    match sort
+        .iter()
+        .find(|(sort_by, _)| matches!(sort_by, SortBy::Author | SortBy::Popularity))
+    {
+        Some((sort_by, _)) => Err(SortValidationError::PublicOnlyKey(*sort_by)),
+        None => Ok(sort),
+    }
+}
+
 #[derive(Deserialize)]
-pub struct QuerySpellsRequest {
+pub struct GetSpellsRequest {
+    pub sort: Option<String>,
+}
+
+impl GetSpellsRequest {
+    pub fn sort_keys(&self) -> Result<Vec<SortKey>, SortValidationError> {
+        private_sort_keys(sort_keys(self.sort.as_deref(), false)?)
+    }
+}
+
+/// The filters both queries of spells have, each field narrows the spells down further. The
+/// filter tree expresses anything the single fields can't.
+#[derive(Deserialize, Clone)]
+pub struct SpellFilters {
     pub name: Option<String>,
-    pub level: Option<String>,
-    pub casting_time: Option<String>,
-    pub magic_school: Option<String>,
+    pub level: Option<i32>,
+    pub level_min: Option<i32>,
+    pub level_max: Option<i32>,
+    pub casting_time_unit: Option<String>,
+    pub casting_time_quantity: Option<i32>,
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub magic_school: Option<Vec<SpellSchool>>,
     pub concentration: Option<bool>,
-    pub range: Option<String>,
-    pub duration: Option<String>,
+    pub range_type: Option<String>,
+    pub range_min_feet: Option<i32>,
+    pub area_shape: Option<String>,
+    pub has_area: Option<bool>,
+    pub duration_unit: Option<String>,
+    pub duration_min_minutes: Option<i32>,
+    pub verbal: Option<bool>,
+    pub somatic: Option<bool>,
+    pub material: Option<bool>,
+    pub costly_material: Option<bool>,
+    pub ritual: Option<bool>,
+    pub classes: Option<Vec<String>>,
+    pub classes_match: Option<ClassMatch>,
+    pub damage_type: Option<String>,
+    pub healing: Option<bool>,
+    pub save_ability: Option<String>,
+    pub attack_type: Option<String>,
+    pub condition: Option<String>,
+    pub tags_any: Option<Vec<String>>,
+    pub tags_all: Option<Vec<String>>,
+    pub source: Option<String>,
+    pub is_homebrew: Option<bool>,
+    pub license: Option<String>,
+    pub updated_since: Option<DateTime<Utc>>,
+    pub filter: Option<Filter>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
+pub struct QuerySpellsRequest {
+    pub q: Option<String>,
+    #[serde(flatten)]
+    pub filters: SpellFilters,
+    pub fuzzy: Option<bool>,
+    pub sort: Option<String>,
+}
+
+#[derive(Deserialize, Clone)]
 pub struct QueryPublicSpellsRequest {
-    pub name: Option<String>,
-    pub level: Option<String>,
-    pub casting_time: Option<String>,
-    pub magic_school: Option<String>,
-    pub concentration: Option<bool>,
-    pub range: Option<String>,
-    pub duration: Option<String>,
+    pub q: Option<String>,
+    #[serde(flatten)]
+    pub filters: SpellFilters,
+    pub fuzzy: Option<bool>,
+    pub sort: Option<String>,
     pub username: Option<String>,
 }
+
+impl QuerySpellsRequest {
+    pub fn search(&self) -> SpellSearch {
+        SpellSearch::new(self.q.as_deref(), self.filters.name.as_deref(), self.fuzzy)
+    }
+
+    pub fn sort_keys(&self) -> Result<Vec<SortKey>, SortValidationError> {
+        private_sort_keys(sort_keys(self.sort.as_deref(), !self.search().is_empty())?)
+    }
+}
+
+impl QueryPublicSpellsRequest {
+    pub fn search(&self) -> SpellSearch {
+        SpellSearch::new(self.q.as_deref(), self.filters.name.as_deref(), self.fuzzy)
+    }
+
+    pub fn sort_keys(&self) -> Result<Vec<SortKey>, SortValidationError> {
+        sort_keys(self.sort.as_deref(), !self.search().is_empty())
+    }
+}
+
+/// What a query ranks spells by, the words of its full text search or else the name it fuzzily
+/// matches. Blank searches are no searches.
+#[derive(Default)]
+pub struct SpellSearch {
+    pub text: Option<String>,
+    pub fuzzy_name: Option<String>,
+}
+
+impl SpellSearch {
+    fn new(q: Option<&str>, name: Option<&str>, fuzzy: Option<bool>) -> Self {
+        let non_blank = |value: Option<&str>| {
+            value
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        SpellSearch {
+            text: non_blank(q),
+            fuzzy_name: non_blank(name).filter(|_| fuzzy.unwrap_or_default()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_none() && self.fuzzy_name.is_none()
+    }
+}
+
+/// Accepts a single value as well as a list of values.
+fn one_or_many<'de, D, T>(deserializer: D) -> Result<Option<Vec<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let values = match Option::<serde_json::Value>::deserialize(deserializer)? {
+        Some(serde_json::Value::Array(values)) => values,
+        Some(value) => vec![value],
+        None => return Ok(None),
+    };
+    values
+        .into_iter()
+        .map(|value| serde_json::from_value(value).map_err(serde::de::Error::custom))
+        .collect::<Result<_, _>>()
+        .map(Some)
+}
+
+#[derive(Deserialize)]
+pub struct DescriptionPreviewRequest {
+    pub description: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn merged(mut target: serde_json::Value, patch: serde_json::Value) -> serde_json::Value {
+        merge_patch(&mut target, &patch);
+        target
+    }
+
+    #[test]
+    fn merge_patch_replaces_and_keeps_fields() {
+        assert_eq!(
+            merged(
+                json!({ "name": "Fireball", "level": 3, "ritual": false }),
+                json!({ "level": 4 })
+            ),
+            json!({ "name": "Fireball", "level": 4, "ritual": false })
+        );
+        assert_eq!(
+            merged(
+                json!({ "name": "Fireball" }),
+                json!({ "source": "Handbook" })
+            ),
+            json!({ "name": "Fireball", "source": "Handbook" })
+        );
+    }
+
+    #[test]
+    fn merge_patch_clears_null_fields() {
+        assert_eq!(
+            merged(
+                json!({ "name": "Fireball", "source": "Handbook" }),
+                json!({ "source": null, "page": null })
+            ),
+            json!({ "name": "Fireball" })
+        );
+    }
+
+    #[test]
+    fn merge_patch_replaces_arrays_as_a_whole() {
+        assert_eq!(
+            merged(
+                json!({ "classes": ["Wizard", "Sorcerer"] }),
+                json!({ "classes": ["Bard"] })
+            ),
+            json!({ "classes": ["Bard"] })
+        );
+    }
+
+    #[test]
+    fn merge_patch_merges_nested_objects() {
+        assert_eq!(
+            merged(
+                json!({ "a": { "b": 1, "c": 2 }, "d": 3 }),
+                json!({ "a": { "b": null, "e": { "f": 4 } } })
+            ),
+            json!({ "a": { "c": 2, "e": { "f": 4 } }, "d": 3 })
+        );
+        assert_eq!(
+            merged(
+                json!({ "a": "text" }),
+                json!({ "a": { "b": null, "c": 1 } })
+            ),
+            json!({ "a": { "c": 1 } })
+        );
+    }
+
+    #[test]
+    fn patches_reject_fields_spells_do_not_have() {
+        let patch: PatchSpellRequest =
+            serde_json::from_value(json!({ "level": 4, "levle": 3 })).unwrap();
+        let error = patch
+            .apply(json!({ "name": "Fireball", "level": 3 }))
+            .err()
+            .unwrap();
+        assert!(error.is_data());
+        assert_eq!(error.to_string(), "unknown field \"levle\"");
+    }
+
+    #[test]
+    fn merge_patch_replaces_the_target_with_anything_but_an_object() {
+        assert_eq!(merged(json!({ "a": 1 }), json!(["a"])), json!(["a"]));
+        assert_eq!(merged(json!({ "a": 1 }), json!(null)), json!(null));
+        assert_eq!(merged(json!(["a"]), json!({ "a": 1 })), json!({ "a": 1 }));
+        assert_eq!(merged(json!({ "a": 1 }), json!({})), json!({ "a": 1 }));
+    }
+
+    /// Writes sort keys back the way they are read, which is easier to compare.
+    fn written(sort: Vec<SortKey>) -> Vec<String> {
+        sort.into_iter()
+            .map(|(sort_by, sort_order)| match sort_order {
+                SortOrder::Asc => sort_by.to_string(),
+                SortOrder::Desc => format!("-{}", sort_by),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parses_sort_keys_in_order() {
+        assert_eq!(
+            written(parse_sort("-level,name").unwrap()),
+            ["-level", "name"]
+        );
+        assert_eq!(
+            written(parse_sort(" created_at , -updated_at ,").unwrap()),
+            ["created_at", "-updated_at"]
+        );
+        assert!(parse_sort("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_unknown_sort_keys() {
+        for (sort, key) in [
+            ("colour", "colour"),
+            ("name,-Level", "Level"),
+            ("--name", "-name"),
+        ] {
+            assert!(matches!(
+                parse_sort(sort),
+                Err(SortValidationError::InvalidKey(invalid)) if invalid == key
+            ));
+        }
+    }
+
+    #[test]
+    fn sorts_searches_by_relevance_unless_sorted_otherwise() {
+        assert_eq!(written(sort_keys(None, true).unwrap()), ["-relevance"]);
+        assert_eq!(written(sort_keys(Some(""), true).unwrap()), ["-relevance"]);
+        assert_eq!(written(sort_keys(Some("name"), true).unwrap()), ["name"]);
+        assert!(sort_keys(None, false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn sorts_by_relevance_only_when_searching() {
+        assert!(matches!(
+            sort_keys(Some("name,-relevance"), false),
+            Err(SortValidationError::RelevanceWithoutSearch)
+        ));
+        assert_eq!(
+            written(sort_keys(Some("relevance"), true).unwrap()),
+            ["relevance"]
+        );
+    }
+
+    #[test]
+    fn sorts_own_spells_by_neither_author_nor_popularity() {
+        for sort in ["author", "name,-popularity"] {
+            assert!(matches!(
+                private_sort_keys(parse_sort(sort).unwrap()),
+                Err(SortValidationError::PublicOnlyKey(
+                    SortBy::Author | SortBy::Popularity
+                ))
+            ));
+        }
+        assert_eq!(
+            written(private_sort_keys(parse_sort("-level,name").unwrap()).unwrap()),
+            ["-level", "name"]
+        );
+    }
+}
@@ -1,2 +1,5 @@
+pub mod pages;
+pub mod revisions;
+pub mod schools;
 pub mod spells;
 pub mod users;
@@ -0,0 +1,23 @@
+use serde::Serialize;
+
+use crate::{models::pages::Page, IntoCollection, IntoResource};
+
+#[derive(Serialize)]
+pub struct PageResource<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+    pub total: Option<i64>,
+}
+
+impl<T, R> IntoResource<PageResource<R>> for Page<T>
+where
+    Vec<T>: IntoCollection<R>,
+{
+    fn into_resource(self) -> PageResource<R> {
+        PageResource {
+            items: self.items.into_collection(),
+            next_cursor: self.next_cursor.map(|cursor| cursor.encode()),
+            total: self.total,
+        }
+    }
+}
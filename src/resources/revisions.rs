@@ -0,0 +1,83 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{
+    models::revisions::{FieldChange, SpellRevisionDetails},
+    IntoCollection, IntoResource,
+};
+
+#[derive(Serialize)]
+pub struct SpellRevisionResource {
+    pub revision: i32,
+    pub author: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl IntoResource<SpellRevisionResource> for SpellRevisionDetails {
+    fn into_resource(self) -> SpellRevisionResource {
+        let (revision, user) = self;
+        SpellRevisionResource {
+            revision: revision.revision,
+            author: user.username,
+            created_at: revision.created_at,
+        }
+    }
+}
+
+impl IntoCollection<SpellRevisionResource> for Vec<SpellRevisionDetails> {
+    fn into_collection(self) -> Vec<SpellRevisionResource> {
+        self.into_iter()
+            .map(|revision| revision.into_resource())
+            .collect()
+    }
+}
+
+#[derive(Serialize)]
+pub struct SpellSnapshotResource {
+    pub revision: i32,
+    pub author: String,
+    pub created_at: DateTime<Utc>,
+    pub spell: serde_json::Value,
+}
+
+impl IntoResource<SpellSnapshotResource> for SpellRevisionDetails {
+    fn into_resource(self) -> SpellSnapshotResource {
+        let (revision, user) = self;
+        let mut spell = revision.snapshot;
+        // the school is shown by name, its id is only kept to revert to it
+        if let serde_json::Value::Object(fields) = &mut spell {
+            fields.remove("homebrew_school_id");
+        }
+        SpellSnapshotResource {
+            revision: revision.revision,
+            author: user.username,
+            created_at: revision.created_at,
+            spell,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct FieldChangeResource {
+    pub field: String,
+    pub from: serde_json::Value,
+    pub to: serde_json::Value,
+}
+
+impl IntoResource<FieldChangeResource> for FieldChange {
+    fn into_resource(self) -> FieldChangeResource {
+        FieldChangeResource {
+            field: self.field,
+            from: self.from,
+            to: self.to,
+        }
+    }
+}
+
+impl IntoCollection<FieldChangeResource> for Vec<FieldChange> {
+    fn into_collection(self) -> Vec<FieldChangeResource> {
+        self.into_iter()
+            .map(|change| change.into_resource())
+            .collect()
+    }
+}
@@ -0,0 +1,33 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{models::schools::HomebrewSchool, IntoCollection, IntoResource};
+
+#[derive(Serialize)]
+pub struct HomebrewSchoolResource {
+    pub name: String,
+    pub description: Option<String>,
+    pub shared: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl IntoResource<HomebrewSchoolResource> for HomebrewSchool {
+    fn into_resource(self) -> HomebrewSchoolResource {
+        HomebrewSchoolResource {
+            name: self.name,
+            description: self.description,
+            shared: self.shared,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+impl IntoCollection<HomebrewSchoolResource> for Vec<HomebrewSchool> {
+    fn into_collection(self) -> Vec<HomebrewSchoolResource> {
+        self.into_iter()
+            .map(|school| school.into_resource())
+            .collect()
+    }
+}
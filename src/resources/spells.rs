@@ -1,42 +1,212 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
 use serde::Serialize;
 
 use crate::{
-    models::{spells::Spell, users::User},
+    enums::CastingTimeUnit,
+    images::image_url,
+    markdown::{clean_snippet, render_description},
+    models::{
+        schools::HomebrewSchool,
+        spells::{PublicSpellDetails, Spell, SpellDetails, SpellEffect, SpellMatch},
+    },
     IntoCollection, IntoResource,
 };
 
+/// Renders a numeric spell level the way it is printed on a spell card.
+fn display_level(level: i32) -> String {
+    match level {
+        0 => "Cantrip".to_string(),
+        n => format!("Level {}", n),
+    }
+}
+
+/// Names the school of a spell, which is either a standard or a homebrew school.
+fn display_school(spell: &Spell, homebrew_school: Option<&HomebrewSchool>) -> String {
+    match (spell.magic_school, homebrew_school) {
+        (Some(magic_school), _) => magic_school.to_string(),
+        (None, Some(homebrew_school)) => homebrew_school.name.to_string(),
+        (None, None) => String::new(),
+    }
+}
+
+/// Renders a casting time like "1 Action", "10 Minutes" or "1 Reaction, which you take when ...".
+fn display_casting_time(spell: &Spell) -> String {
+    let unit = match CastingTimeUnit::from_str(&spell.casting_time_unit) {
+        Ok(CastingTimeUnit::Action) => "Action",
+        Ok(CastingTimeUnit::BonusAction) => "Bonus Action",
+        Ok(CastingTimeUnit::Reaction) => "Reaction",
+        Ok(CastingTimeUnit::Minute) => "Minute",
+        Ok(CastingTimeUnit::Hour) => "Hour",
+        Err(_) => &spell.casting_time_unit,
+    };
+    let mut casting_time = format!("{} {}", spell.casting_time_quantity, unit);
+    if spell.casting_time_quantity > 1 {
+        casting_time.push('s');
+    }
+    if let Some(reaction_trigger) = &spell.reaction_trigger {
+        casting_time.push_str(&format!(", {}", reaction_trigger));
+    }
+    casting_time
+}
+
+/// Renders a range like "Touch", "150 feet" or "Self (15-foot Cone)".
+fn display_range(spell: &Spell) -> String {
+    let mut range = match (spell.range_distance, spell.range_unit.as_deref()) {
+        (Some(1), Some("Miles")) => "1 mile".to_string(),
+        (Some(distance), Some("Miles")) => format!("{} miles", distance),
+        (Some(distance), _) => format!("{} feet", distance),
+        (None, _) => spell.range_type.to_string(),
+    };
+    if let (Some(area_shape), Some(area_size)) = (&spell.area_shape, spell.area_size) {
+        range.push_str(&format!(" ({}-foot {})", area_size, area_shape));
+    }
+    range
+}
+
+/// Renders a duration like "Instantaneous", "1 round" or "Concentration, up to 10 minutes".
+fn display_duration(spell: &Spell) -> String {
+    let duration = match (spell.duration_quantity, spell.duration_unit.as_str()) {
+        (Some(1), unit) => format!("1 {}", unit.to_lowercase()),
+        (Some(quantity), unit) => format!("{} {}s", quantity, unit.to_lowercase()),
+        (None, "UntilDispelled") => "Until dispelled".to_string(),
+        (None, unit) => unit.to_string(),
+    };
+    match (spell.duration_up_to, spell.concentration) {
+        (true, true) => format!("Concentration, up to {}", duration),
+        (true, false) => format!("Up to {}", duration),
+        (false, _) => duration,
+    }
+}
+
 #[derive(Serialize)]
 pub struct SpellResource {
     pub id: String,
     pub name: String,
-    pub level: String,
+    pub level: i32,
+    pub level_display: String,
     pub casting_time: String,
+    pub casting_time_unit: String,
+    pub casting_time_quantity: i32,
+    pub reaction_trigger: Option<String>,
     pub magic_school: String,
+    pub standard_school: bool,
+    pub school_description: Option<String>,
     pub concentration: bool,
     pub range: String,
+    pub range_type: String,
+    pub range_distance: Option<i32>,
+    pub range_unit: Option<String>,
+    pub area_shape: Option<String>,
+    pub area_size: Option<i32>,
     pub duration: String,
+    pub duration_unit: String,
+    pub duration_quantity: Option<i32>,
+    pub duration_up_to: bool,
     pub description: String,
+    pub description_html: String,
+    pub image_url: Option<String>,
     pub published: bool,
+    pub verbal: bool,
+    pub somatic: bool,
+    pub material: bool,
+    pub material_description: Option<String>,
+    pub material_cost: Option<i32>,
+    pub material_consumed: bool,
+    pub ritual: bool,
+    pub classes: Vec<String>,
+    pub tags: Vec<String>,
+    pub damage_dice: Option<String>,
+    pub healing_dice: Option<String>,
+    pub higher_levels: Option<String>,
+    pub higher_level_dice: Option<String>,
+    pub cantrip_scaling: bool,
+    pub damage_types: Vec<String>,
+    pub save_ability: Option<String>,
+    pub attack_type: Option<String>,
+    pub conditions: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub version: i32,
+    pub source: Option<String>,
+    pub page: Option<i32>,
+    pub is_homebrew: bool,
+    pub license: Option<String>,
+    pub license_label: Option<String>,
 }
 
-impl IntoResource<SpellResource> for Spell {
+impl IntoResource<SpellResource> for SpellDetails {
     fn into_resource(self) -> SpellResource {
+        let (spell, classes, tags, homebrew_school) = self;
+        let casting_time = display_casting_time(&spell);
+        let range = display_range(&spell);
+        let duration = display_duration(&spell);
+        let magic_school = display_school(&spell, homebrew_school.as_ref());
+        // spells saved before descriptions were markdown have no rendered description yet
+        let description_html = spell
+            .description_html
+            .unwrap_or_else(|| render_description(&spell.description));
         SpellResource {
-            id: self.nanoid,
-            name: self.name,
-            level: self.level,
-            casting_time: self.casting_time,
-            magic_school: self.magic_school,
-            concentration: self.concentration,
-            range: self.range,
-            duration: self.duration,
-            description: self.description,
-            published: self.published,
+            id: spell.nanoid,
+            name: spell.name,
+            level: spell.level,
+            level_display: display_level(spell.level),
+            casting_time,
+            casting_time_unit: spell.casting_time_unit,
+            casting_time_quantity: spell.casting_time_quantity,
+            reaction_trigger: spell.reaction_trigger,
+            magic_school,
+            standard_school: spell.magic_school.is_some(),
+            school_description: homebrew_school.and_then(|school| school.description),
+            concentration: spell.concentration,
+            range,
+            range_type: spell.range_type,
+            range_distance: spell.range_distance,
+            range_unit: spell.range_unit,
+            area_shape: spell.area_shape,
+            area_size: spell.area_size,
+            duration,
+            duration_unit: spell.duration_unit,
+            duration_quantity: spell.duration_quantity,
+            duration_up_to: spell.duration_up_to,
+            description: spell.description,
+            description_html,
+            image_url: spell.image.as_deref().map(image_url),
+            published: spell.published,
+            verbal: spell.verbal,
+            somatic: spell.somatic,
+            material: spell.material,
+            material_description: spell.material_description,
+            material_cost: spell.material_cost,
+            material_consumed: spell.material_consumed,
+            ritual: spell.ritual,
+            classes: classes.into_iter().map(|c| c.caster_class).collect(),
+            tags: tags.into_iter().map(|t| t.tag).collect(),
+            damage_dice: spell.damage_dice,
+            healing_dice: spell.healing_dice,
+            higher_levels: spell.higher_levels,
+            higher_level_dice: spell.higher_level_dice,
+            cantrip_scaling: spell.cantrip_scaling,
+            damage_types: spell.damage_types,
+            save_ability: spell.save_ability,
+            attack_type: spell.attack_type,
+            conditions: spell.conditions,
+            created_at: spell.created_at,
+            updated_at: spell.updated_at,
+            deleted_at: spell.deleted_at,
+            version: spell.version,
+            source: spell.source,
+            page: spell.page,
+            is_homebrew: spell.is_homebrew,
+            license: spell.license,
+            license_label: spell.license_label,
         }
     }
 }
 
-impl IntoCollection<SpellResource> for Vec<Spell> {
+impl IntoCollection<SpellResource> for Vec<SpellDetails> {
     fn into_collection(self) -> Vec<SpellResource> {
         self.into_iter()
             .map(|spell| spell.into_resource())
@@ -48,37 +218,237 @@ impl IntoCollection<SpellResource> for Vec<Spell> {
 pub struct PublicSpellResource {
     pub id: String,
     pub name: String,
-    pub level: String,
+    pub level: i32,
+    pub level_display: String,
     pub casting_time: String,
+    pub casting_time_unit: String,
+    pub casting_time_quantity: i32,
+    pub reaction_trigger: Option<String>,
     pub magic_school: String,
+    pub standard_school: bool,
+    pub school_description: Option<String>,
     pub concentration: bool,
     pub range: String,
+    pub range_type: String,
+    pub range_distance: Option<i32>,
+    pub range_unit: Option<String>,
+    pub area_shape: Option<String>,
+    pub area_size: Option<i32>,
     pub duration: String,
+    pub duration_unit: String,
+    pub duration_quantity: Option<i32>,
+    pub duration_up_to: bool,
     pub description: String,
+    pub description_html: String,
+    pub image_url: Option<String>,
+    pub verbal: bool,
+    pub somatic: bool,
+    pub material: bool,
+    pub material_description: Option<String>,
+    pub material_cost: Option<i32>,
+    pub material_consumed: bool,
+    pub ritual: bool,
+    pub classes: Vec<String>,
+    pub tags: Vec<String>,
+    pub damage_dice: Option<String>,
+    pub healing_dice: Option<String>,
+    pub higher_levels: Option<String>,
+    pub higher_level_dice: Option<String>,
+    pub cantrip_scaling: bool,
+    pub damage_types: Vec<String>,
+    pub save_ability: Option<String>,
+    pub attack_type: Option<String>,
+    pub conditions: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub source: Option<String>,
+    pub page: Option<i32>,
+    pub is_homebrew: bool,
+    pub license: Option<String>,
+    pub license_label: Option<String>,
     pub username: String,
 }
 
-impl IntoResource<PublicSpellResource> for (Spell, User) {
+impl IntoResource<PublicSpellResource> for PublicSpellDetails {
     fn into_resource(self) -> PublicSpellResource {
+        let (spell, user, classes, tags, homebrew_school) = self;
+        let casting_time = display_casting_time(&spell);
+        let range = display_range(&spell);
+        let duration = display_duration(&spell);
+        let magic_school = display_school(&spell, homebrew_school.as_ref());
+        // spells saved before descriptions were markdown have no rendered description yet
+        let description_html = spell
+            .description_html
+            .unwrap_or_else(|| render_description(&spell.description));
         PublicSpellResource {
-            id: self.0.nanoid,
-            name: self.0.name,
-            level: self.0.level,
-            casting_time: self.0.casting_time,
-            magic_school: self.0.magic_school,
-            concentration: self.0.concentration,
-            range: self.0.range,
-            duration: self.0.duration,
-            description: self.0.description,
-            username: self.1.username,
+            id: spell.nanoid,
+            name: spell.name,
+            level: spell.level,
+            level_display: display_level(spell.level),
+            casting_time,
+            casting_time_unit: spell.casting_time_unit,
+            casting_time_quantity: spell.casting_time_quantity,
+            reaction_trigger: spell.reaction_trigger,
+            magic_school,
+            standard_school: spell.magic_school.is_some(),
+            // the description of a homebrew school is only public if its author shares it
+            school_description: homebrew_school
+                .filter(|school| school.shared)
+                .and_then(|school| school.description),
+            concentration: spell.concentration,
+            range,
+            range_type: spell.range_type,
+            range_distance: spell.range_distance,
+            range_unit: spell.range_unit,
+            area_shape: spell.area_shape,
+            area_size: spell.area_size,
+            duration,
+            duration_unit: spell.duration_unit,
+            duration_quantity: spell.duration_quantity,
+            duration_up_to: spell.duration_up_to,
+            description: spell.description,
+            description_html,
+            image_url: spell.image.as_deref().map(image_url),
+            verbal: spell.verbal,
+            somatic: spell.somatic,
+            material: spell.material,
+            material_description: spell.material_description,
+            material_cost: spell.material_cost,
+            material_consumed: spell.material_consumed,
+            ritual: spell.ritual,
+            classes: classes.into_iter().map(|c| c.caster_class).collect(),
+            tags: tags.into_iter().map(|t| t.tag).collect(),
+            damage_dice: spell.damage_dice,
+            healing_dice: spell.healing_dice,
+            higher_levels: spell.higher_levels,
+            higher_level_dice: spell.higher_level_dice,
+            cantrip_scaling: spell.cantrip_scaling,
+            damage_types: spell.damage_types,
+            save_ability: spell.save_ability,
+            attack_type: spell.attack_type,
+            conditions: spell.conditions,
+            created_at: spell.created_at,
+            updated_at: spell.updated_at,
+            source: spell.source,
+            page: spell.page,
+            is_homebrew: spell.is_homebrew,
+            license: spell.license,
+            license_label: spell.license_label,
+            username: user.username,
         }
     }
 }
 
-impl IntoCollection<PublicSpellResource> for Vec<(Spell, User)> {
+impl IntoCollection<PublicSpellResource> for Vec<PublicSpellDetails> {
     fn into_collection(self) -> Vec<PublicSpellResource> {
         self.into_iter()
             .map(|spell_with_user| spell_with_user.into_resource())
             .collect()
     }
 }
+
+#[derive(Serialize)]
+pub struct SpellMatchResource<T> {
+    #[serde(flatten)]
+    pub spell: T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snippet: Option<String>,
+}
+
+impl<T, R> IntoResource<SpellMatchResource<R>> for SpellMatch<T>
+where
+    T: IntoResource<R>,
+{
+    fn into_resource(self) -> SpellMatchResource<R> {
+        let (spell, snippet) = self;
+        SpellMatchResource {
+            spell: spell.into_resource(),
+            snippet: snippet.map(|snippet| clean_snippet(&snippet)),
+        }
+    }
+}
+
+impl<T, R> IntoCollection<SpellMatchResource<R>> for Vec<SpellMatch<T>>
+where
+    T: IntoResource<R>,
+{
+    fn into_collection(self) -> Vec<SpellMatchResource<R>> {
+        self.into_iter()
+            .map(|spell| spell.into_resource())
+            .collect()
+    }
+}
+
+#[derive(Serialize)]
+pub struct SpellEffectResource {
+    pub slot_level: Option<i32>,
+    pub character_level: Option<i32>,
+    pub damage_dice: Option<String>,
+    pub healing_dice: Option<String>,
+}
+
+impl IntoResource<SpellEffectResource> for SpellEffect {
+    fn into_resource(self) -> SpellEffectResource {
+        SpellEffectResource {
+            slot_level: self.slot_level,
+            character_level: self.character_level,
+            damage_dice: self.damage_dice.map(|dice| dice.to_string()),
+            healing_dice: self.healing_dice.map(|dice| dice.to_string()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct TagResource {
+    pub tag: String,
+    pub count: i64,
+}
+
+impl IntoResource<TagResource> for (String, i64) {
+    fn into_resource(self) -> TagResource {
+        let (tag, count) = self;
+        TagResource { tag, count }
+    }
+}
+
+impl IntoCollection<TagResource> for Vec<(String, i64)> {
+    fn into_collection(self) -> Vec<TagResource> {
+        self.into_iter()
+            .map(|tag_count| tag_count.into_resource())
+            .collect()
+    }
+}
+
+#[derive(Serialize)]
+pub struct SpellSuggestionResource {
+    pub id: String,
+    pub name: String,
+}
+
+impl IntoResource<SpellSuggestionResource> for Spell {
+    fn into_resource(self) -> SpellSuggestionResource {
+        SpellSuggestionResource {
+            id: self.nanoid,
+            name: self.name,
+        }
+    }
+}
+
+impl IntoCollection<SpellSuggestionResource> for Vec<Spell> {
+    fn into_collection(self) -> Vec<SpellSuggestionResource> {
+        self.into_iter()
+            .map(|spell| spell.into_resource())
+            .collect()
+    }
+}
+
+#[derive(Serialize)]
+pub struct SpellNotFoundResource {
+    pub message: String,
+    pub did_you_mean: Vec<SpellSuggestionResource>,
+}
+
+#[derive(Serialize)]
+pub struct DescriptionPreviewResource {
+    pub description_html: String,
+}
@@ -1,19 +1,138 @@
 // @generated automatically by Diesel CLI.
 
+pub mod sql_types {
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "magic_school"))]
+    pub struct MagicSchool;
+
+    #[derive(diesel::query_builder::QueryId, Clone, diesel::sql_types::SqlType)]
+    #[diesel(postgres_type(name = "tsvector", schema = "pg_catalog"))]
+    pub struct Tsvector;
+}
+
 diesel::table! {
+    homebrew_schools (id) {
+        id -> Int4,
+        user_id -> Int4,
+        name -> Varchar,
+        description -> Nullable<Text>,
+        shared -> Bool,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    spell_classes (spell_id, caster_class) {
+        spell_id -> Int4,
+        caster_class -> Varchar,
+    }
+}
+
+diesel::table! {
+    spell_revisions (id) {
+        id -> Int4,
+        spell_id -> Int4,
+        revision -> Int4,
+        user_id -> Int4,
+        snapshot -> Jsonb,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    spell_tags (spell_id, tag) {
+        spell_id -> Int4,
+        tag -> Varchar,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use super::sql_types::MagicSchool;
+    use super::sql_types::Tsvector;
+
     spells (id) {
         id -> Int4,
         name -> Varchar,
-        level -> Varchar,
-        casting_time -> Varchar,
-        magic_school -> Varchar,
+        level -> Int4,
+        magic_school -> Nullable<MagicSchool>,
         concentration -> Bool,
-        range -> Varchar,
-        duration -> Varchar,
         description -> Text,
         user_id -> Int4,
         published -> Bool,
         nanoid -> Varchar,
+        verbal -> Bool,
+        somatic -> Bool,
+        material -> Bool,
+        material_description -> Nullable<Text>,
+        material_cost -> Nullable<Int4>,
+        material_consumed -> Bool,
+        ritual -> Bool,
+        damage_dice -> Nullable<Varchar>,
+        higher_levels -> Nullable<Text>,
+        higher_level_dice -> Nullable<Varchar>,
+        cantrip_scaling -> Bool,
+        casting_time_unit -> Varchar,
+        casting_time_quantity -> Int4,
+        reaction_trigger -> Nullable<Text>,
+        range_type -> Varchar,
+        range_distance -> Nullable<Int4>,
+        range_unit -> Nullable<Varchar>,
+        area_shape -> Nullable<Varchar>,
+        area_size -> Nullable<Int4>,
+        range_feet -> Nullable<Int4>,
+        duration_unit -> Varchar,
+        duration_quantity -> Nullable<Int4>,
+        duration_up_to -> Bool,
+        duration_seconds -> Nullable<Int4>,
+        healing_dice -> Nullable<Varchar>,
+        damage_types -> Array<Text>,
+        save_ability -> Nullable<Varchar>,
+        attack_type -> Nullable<Varchar>,
+        conditions -> Array<Text>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        source -> Nullable<Varchar>,
+        page -> Nullable<Int4>,
+        is_homebrew -> Bool,
+        license -> Nullable<Varchar>,
+        license_label -> Nullable<Varchar>,
+        homebrew_school_id -> Nullable<Int4>,
+        description_html -> Nullable<Text>,
+        deleted_at -> Nullable<Timestamptz>,
+        version -> Int4,
+        image -> Nullable<Varchar>,
+        copied_from_id -> Nullable<Int4>,
+        search_vector -> Tsvector,
+    }
+}
+
+diesel::table! {
+    unparsed_casting_times (spell_id) {
+        spell_id -> Int4,
+        casting_time -> Varchar,
+    }
+}
+
+diesel::table! {
+    unparsed_durations (spell_id) {
+        spell_id -> Int4,
+        duration -> Varchar,
+    }
+}
+
+diesel::table! {
+    unparsed_levels (spell_id) {
+        spell_id -> Int4,
+        level -> Varchar,
+    }
+}
+
+diesel::table! {
+    unparsed_ranges (spell_id) {
+        spell_id -> Int4,
+        range -> Varchar,
     }
 }
 
@@ -22,12 +141,32 @@ diesel::table! {
         id -> Int4,
         username -> Varchar,
         key_hash -> Varchar,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
     }
 }
 
+diesel::joinable!(homebrew_schools -> users (user_id));
+diesel::joinable!(spell_classes -> spells (spell_id));
+diesel::joinable!(spell_revisions -> spells (spell_id));
+diesel::joinable!(spell_revisions -> users (user_id));
+diesel::joinable!(spell_tags -> spells (spell_id));
+diesel::joinable!(spells -> homebrew_schools (homebrew_school_id));
 diesel::joinable!(spells -> users (user_id));
+diesel::joinable!(unparsed_casting_times -> spells (spell_id));
+diesel::joinable!(unparsed_durations -> spells (spell_id));
+diesel::joinable!(unparsed_levels -> spells (spell_id));
+diesel::joinable!(unparsed_ranges -> spells (spell_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    homebrew_schools,
+    spell_classes,
+    spell_revisions,
+    spell_tags,
     spells,
+    unparsed_casting_times,
+    unparsed_durations,
+    unparsed_levels,
+    unparsed_ranges,
     users,
 );
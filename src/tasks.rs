@@ -0,0 +1,62 @@
+use std::{env, time::Duration};
+
+use chrono::{TimeDelta, Utc};
+use dotenvy::dotenv;
+
+use crate::{establish_connection, images, repositories};
+
+/// How often the trash is checked for spells past their retention.
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Trashed spells are kept for `TRASH_RETENTION_DAYS` days, 30 if it isn't set.
+pub fn trash_retention() -> TimeDelta {
+    dotenv().ok();
+
+    let days = match env::var("TRASH_RETENTION_DAYS") {
+        Ok(days) => days
+            .parse()
+            .expect("environment variable TRASH_RETENTION_DAYS must be a number of days"),
+        Err(_) => 30,
+    };
+    TimeDelta::days(days)
+}
+
+/// Erases trashed spells once they have been in the trash for longer than the retention.
+pub async fn purge_trash() {
+    let retention = trash_retention();
+    let mut interval = tokio::time::interval(PURGE_INTERVAL);
+    loop {
+        interval.tick().await;
+        let conn = &mut establish_connection();
+        match repositories::spells::purge_trash(conn, Utc::now() - retention) {
+            Ok(0) => {}
+            Ok(purged) => println!("purged {} spell(s) from the trash", purged),
+            Err(e) => eprintln!("Failed to purge trash: {}", e),
+        }
+    }
+}
+
+/// How often the image directory is checked for illustrations no spell uses anymore.
+const IMAGE_CLEANUP_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Removes stored illustrations once the last spell using them was erased or got another one.
+/// Copies of a spell share its illustration, so it can't be removed along with a single spell.
+pub async fn remove_unused_images() {
+    let mut interval = tokio::time::interval(IMAGE_CLEANUP_INTERVAL);
+    loop {
+        interval.tick().await;
+        let conn = &mut establish_connection();
+        let used_images = match repositories::spells::get_used_images(conn) {
+            Ok(used_images) => used_images.into_iter().collect(),
+            Err(e) => {
+                eprintln!("Failed to retrieve used images: {}", e);
+                continue;
+            }
+        };
+        match images::remove_unused_images(&used_images) {
+            Ok(0) => {}
+            Ok(removed) => println!("removed {} unused image(s)", removed),
+            Err(e) => eprintln!("Failed to remove unused images: {}", e),
+        }
+    }
+}
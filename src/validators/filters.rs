@@ -0,0 +1,205 @@
+use crate::{
+    errors::FilterValidationError,
+    requests::filters::{FieldKind, Filter, FilterField, FilterOperator, FilterValue},
+    Validate,
+};
+
+impl Validate<FilterValidationError> for Filter {
+    fn validate(&self) -> Result<(), FilterValidationError> {
+        match self {
+            Filter::And(filters) | Filter::Or(filters) => {
+                filters.iter().try_for_each(|filter| filter.validate())
+            }
+            Filter::Not(filter) => filter.validate(),
+            Filter::Condition(field, operator) => validate_condition(*field, operator),
+        }
+    }
+}
+
+fn validate_condition(
+    field: FilterField,
+    operator: &FilterOperator,
+) -> Result<(), FilterValidationError> {
+    let kind = field.kind();
+    let supported = match operator {
+        FilterOperator::Eq(_) => !matches!(kind, FieldKind::List | FieldKind::Time),
+        FilterOperator::In(_) => !matches!(kind, FieldKind::Boolean | FieldKind::Time),
+        FilterOperator::Contains(_) => matches!(kind, FieldKind::Text | FieldKind::List),
+        FilterOperator::StartsWith(_) => matches!(kind, FieldKind::Text),
+        FilterOperator::Range(_) => matches!(kind, FieldKind::Integer | FieldKind::Time),
+    };
+    if !supported {
+        return Err(FilterValidationError::UnsupportedOperator(
+            field,
+            operator.into(),
+        ));
+    }
+
+    let fits = |value: &FilterValue| match kind {
+        FieldKind::Text | FieldKind::Keyword | FieldKind::List => value.text().is_some(),
+        FieldKind::Integer => value.integer().is_some(),
+        FieldKind::Boolean => value.boolean().is_some(),
+        FieldKind::Time => value.time().is_some(),
+    };
+    let valid = match operator {
+        FilterOperator::Eq(value) => fits(value),
+        FilterOperator::In(values) => values.iter().all(fits),
+        FilterOperator::Contains(_) | FilterOperator::StartsWith(_) => true,
+        FilterOperator::Range(range) => {
+            (range.min.is_some() || range.max.is_some())
+                && range.min.iter().chain(&range.max).all(fits)
+        }
+    };
+    if !valid {
+        return Err(FilterValidationError::InvalidValue(field));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::requests::filters::FilterRange;
+
+    fn text(value: &str) -> FilterValue {
+        FilterValue::Text(value.to_string())
+    }
+
+    fn condition(field: FilterField, operator: FilterOperator) -> Filter {
+        Filter::Condition(field, operator)
+    }
+
+    #[test]
+    fn accepts_operators_fields_support() {
+        for filter in [
+            condition(FilterField::Name, FilterOperator::Eq(text("Fireball"))),
+            condition(
+                FilterField::Name,
+                FilterOperator::StartsWith("Fire".to_string()),
+            ),
+            condition(
+                FilterField::School,
+                FilterOperator::In(vec![text("Evocation")]),
+            ),
+            condition(
+                FilterField::Level,
+                FilterOperator::In(vec![FilterValue::Integer(3)]),
+            ),
+            condition(
+                FilterField::Ritual,
+                FilterOperator::Eq(FilterValue::Boolean(true)),
+            ),
+            condition(
+                FilterField::Tags,
+                FilterOperator::Contains("aoe".to_string()),
+            ),
+            condition(
+                FilterField::RangeFeet,
+                FilterOperator::Range(FilterRange {
+                    min: Some(FilterValue::Integer(30)),
+                    max: None,
+                }),
+            ),
+            condition(
+                FilterField::UpdatedAt,
+                FilterOperator::Range(FilterRange {
+                    min: None,
+                    max: Some(text("2026-10-19T09:00:00Z")),
+                }),
+            ),
+        ] {
+            assert!(filter.validate().is_ok());
+        }
+    }
+
+    #[test]
+    fn rejects_operators_fields_do_not_support() {
+        for filter in [
+            condition(FilterField::Classes, FilterOperator::Eq(text("Wizard"))),
+            condition(
+                FilterField::CreatedAt,
+                FilterOperator::Eq(text("2026-10-19T09:00:00Z")),
+            ),
+            condition(FilterField::Concentration, FilterOperator::In(vec![])),
+            condition(
+                FilterField::School,
+                FilterOperator::Contains("Evo".to_string()),
+            ),
+            condition(
+                FilterField::Tags,
+                FilterOperator::StartsWith("ao".to_string()),
+            ),
+            condition(
+                FilterField::Name,
+                FilterOperator::Range(FilterRange {
+                    min: Some(text("A")),
+                    max: None,
+                }),
+            ),
+        ] {
+            assert!(matches!(
+                filter.validate(),
+                Err(FilterValidationError::UnsupportedOperator(..))
+            ));
+        }
+    }
+
+    #[test]
+    fn rejects_values_of_another_kind() {
+        for filter in [
+            condition(FilterField::Level, FilterOperator::Eq(text("3"))),
+            condition(
+                FilterField::Ritual,
+                FilterOperator::Eq(FilterValue::Integer(1)),
+            ),
+            condition(
+                FilterField::Name,
+                FilterOperator::Eq(FilterValue::Boolean(true)),
+            ),
+            condition(
+                FilterField::Level,
+                FilterOperator::In(vec![FilterValue::Integer(1), text("2")]),
+            ),
+            condition(
+                FilterField::CreatedAt,
+                FilterOperator::Range(FilterRange {
+                    min: Some(text("yesterday")),
+                    max: None,
+                }),
+            ),
+            condition(
+                FilterField::Level,
+                FilterOperator::Range(FilterRange {
+                    min: None,
+                    max: None,
+                }),
+            ),
+        ] {
+            assert!(matches!(
+                filter.validate(),
+                Err(FilterValidationError::InvalidValue(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn validates_nested_conditions() {
+        let valid = condition(
+            FilterField::Level,
+            FilterOperator::Eq(FilterValue::Integer(3)),
+        );
+        let invalid = condition(FilterField::Level, FilterOperator::Eq(text("3")));
+
+        assert!(
+            Filter::And(vec![valid.clone(), Filter::Not(Box::new(valid.clone()))])
+                .validate()
+                .is_ok()
+        );
+        assert!(Filter::Or(vec![valid.clone(), invalid.clone()])
+            .validate()
+            .is_err());
+        assert!(Filter::And(vec![valid, Filter::Not(Box::new(invalid))])
+            .validate()
+            .is_err());
+    }
+}
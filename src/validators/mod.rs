@@ -1,2 +1,5 @@
+pub mod filters;
+pub mod pages;
+pub mod schools;
 pub mod spells;
 pub mod users;
@@ -0,0 +1,15 @@
+use crate::{errors::PageValidationError, requests::pages::PageRequest, Validate};
+
+impl Validate<PageValidationError> for PageRequest {
+    fn validate(&self) -> Result<(), PageValidationError> {
+        if let Some(limit) = self.limit {
+            if limit < 1 {
+                return Err(PageValidationError::InvalidLimit(limit));
+            }
+        }
+        if self.cursor.is_some() && self.cursor().is_none() {
+            return Err(PageValidationError::InvalidCursor);
+        }
+        Ok(())
+    }
+}
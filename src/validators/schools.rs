@@ -0,0 +1,40 @@
+use std::str::FromStr;
+
+use crate::{
+    enums::MagicSchool,
+    errors::HomebrewSchoolValidationError,
+    requests::schools::{CreateHomebrewSchoolRequest, UpdateHomebrewSchoolRequest},
+    Validate,
+};
+
+const MAX_SCHOOL_NAME_LENGTH: usize = 50;
+
+impl Validate<HomebrewSchoolValidationError> for CreateHomebrewSchoolRequest {
+    fn validate(&self) -> Result<(), HomebrewSchoolValidationError> {
+        validate_name(&self.name)
+    }
+}
+
+impl Validate<HomebrewSchoolValidationError> for UpdateHomebrewSchoolRequest {
+    fn validate(&self) -> Result<(), HomebrewSchoolValidationError> {
+        if let Some(name) = &self.name {
+            validate_name(name)?;
+        }
+        Ok(())
+    }
+}
+
+/// Homebrew schools can't shadow one of the standard schools, or spells using them would be
+/// ambiguous.
+fn validate_name(name: &str) -> Result<(), HomebrewSchoolValidationError> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_SCHOOL_NAME_LENGTH {
+        return Err(HomebrewSchoolValidationError::InvalidName(name.to_string()));
+    }
+    if MagicSchool::from_str(name).is_ok() {
+        return Err(HomebrewSchoolValidationError::StandardSchoolName(
+            name.to_string(),
+        ));
+    }
+    Ok(())
+}
@@ -1,48 +1,395 @@
-use std::str::FromStr;
-
-use regex::Regex;
+use std::{ops::RangeInclusive, str::FromStr};
 
 use crate::{
-    enums::MagicSchool,
-    errors::SpellValidationError,
-    requests::spells::{CreateSpellRequest, UpdatedSpellData},
+    dice::Dice,
+    enums::{
+        Ability, AreaShape, AttackType, CasterClass, CastingTimeUnit, Condition, DamageType,
+        DistanceUnit, DurationUnit, License, RangeType,
+    },
+    errors::{FilterValidationError, SpellValidationError},
+    requests::spells::{CreateSpellRequest, SpellFilters, SpellTagsRequest},
     Validate,
 };
 
+/// Spell levels range from 0 (cantrips) to 9.
+const SPELL_LEVELS: RangeInclusive<i32> = 0..=9;
+
+/// The most dice and the largest modifier a spell can roll, well beyond any printed spell and
+/// small enough that scaling them to the highest slot or character level never overflows.
+const MAX_DICE: u32 = 100;
+
+/// Range distances are stored in feet as well, a mile is 5280 feet and the most miles still fit
+/// the column.
+const RANGE_DISTANCES: RangeInclusive<i32> = 1..=100_000;
+
+/// Durations are stored in seconds as well, the most days still fit the column.
+const DURATION_QUANTITIES: RangeInclusive<i32> = 1..=10_000;
+
+/// Filters on the least duration are compared in seconds, up to the longest duration there is.
+const DURATION_MIN_MINUTES: RangeInclusive<i32> = 0..=10_000 * 24 * 60;
+
 impl Validate<SpellValidationError> for CreateSpellRequest {
     fn validate(&self) -> Result<(), SpellValidationError> {
-        let level_regex = Regex::new("^Level [1-9]$|^Cantrip$").unwrap();
-        if !level_regex.is_match(&self.level) {
-            return Err(SpellValidationError::InvalidSpellLevel(
-                self.level.to_string(),
-            ));
+        if !SPELL_LEVELS.contains(&self.level) {
+            return Err(SpellValidationError::InvalidSpellLevel(self.level));
         }
-        if MagicSchool::from_str(&self.magic_school).is_err() {
-            return Err(SpellValidationError::InvalidMagicSchool(
-                self.magic_school.to_string(),
-            ));
+        validate_casting_time(
+            &self.casting_time_unit,
+            self.casting_time_quantity,
+            self.reaction_trigger.as_deref(),
+        )?;
+        validate_range(
+            &self.range_type,
+            self.range_distance,
+            self.range_unit.as_deref(),
+        )?;
+        validate_area(self.area_shape.as_deref(), self.area_size)?;
+        validate_duration(
+            &self.duration_unit,
+            self.duration_quantity,
+            self.duration_up_to,
+            self.concentration,
+        )?;
+        if !self.material
+            && (self.material_description.is_some()
+                || self.material_cost.is_some()
+                || self.material_consumed)
+        {
+            return Err(SpellValidationError::MaterialDetailsWithoutMaterialComponent);
+        }
+        if let Some(material_cost) = self.material_cost {
+            if material_cost <= 0 {
+                return Err(SpellValidationError::InvalidMaterialCost(material_cost));
+            }
+        }
+        for class in &self.classes {
+            if CasterClass::from_str(class).is_err() {
+                return Err(SpellValidationError::InvalidCasterClass(class.to_string()));
+            }
+        }
+        let effect_dice =
+            parse_effect_dice(self.damage_dice.as_deref(), self.healing_dice.as_deref())?;
+        let higher_level_dice = self
+            .higher_level_dice
+            .as_deref()
+            .map(parse_dice)
+            .transpose()?;
+        if let Some(higher_level_dice) = higher_level_dice {
+            if self.level == 0 || effect_dice.is_empty() {
+                return Err(SpellValidationError::HigherLevelDiceWithoutLeveledEffect);
+            }
+            validate_higher_level_dice(&effect_dice, higher_level_dice)?;
+        }
+        if self.cantrip_scaling && (self.level != 0 || effect_dice.is_empty()) {
+            return Err(SpellValidationError::CantripScalingWithoutCantripEffect);
         }
+        validate_effect_metadata(
+            &self.damage_types,
+            self.save_ability.as_deref(),
+            self.attack_type.as_deref(),
+            &self.conditions,
+        )?;
+        validate_provenance(
+            self.source.as_deref(),
+            self.page,
+            self.license.as_deref(),
+            self.license_label.as_deref(),
+        )?;
         Ok(())
     }
 }
 
-impl Validate<SpellValidationError> for UpdatedSpellData {
+fn validate_provenance(
+    source: Option<&str>,
+    page: Option<i32>,
+    license: Option<&str>,
+    license_label: Option<&str>,
+) -> Result<(), SpellValidationError> {
+    if let Some(page) = page {
+        if page <= 0 {
+            return Err(SpellValidationError::InvalidPage(page));
+        }
+        if source.is_none() {
+            return Err(SpellValidationError::PageWithoutSource);
+        }
+    }
+
+    if let Some(license) = license {
+        if License::from_str(license).is_err() {
+            return Err(SpellValidationError::InvalidLicense(license.to_string()));
+        }
+        if (license == "Custom") != license_label.is_some() {
+            return Err(SpellValidationError::LicenseLabelWithoutCustomLicense);
+        }
+    }
+
+    Ok(())
+}
+
+/// Tags are trimmed before they are stored, see `SpellTagsRequest::trimmed_tags`.
+const MAX_TAG_LENGTH: usize = 50;
+
+impl Validate<SpellValidationError> for SpellTagsRequest {
     fn validate(&self) -> Result<(), SpellValidationError> {
-        if let Some(level) = &self.level {
-            let level_regex = Regex::new("^Level [1-9]$|^Cantrip$").unwrap();
-            if !level_regex.is_match(level) {
-                return Err(SpellValidationError::InvalidSpellLevel(level.to_string()));
+        for tag in self.trimmed_tags() {
+            if tag.is_empty() || tag.chars().count() > MAX_TAG_LENGTH {
+                return Err(SpellValidationError::InvalidTag(tag.to_string()));
             }
         }
+        Ok(())
+    }
+}
 
-        if let Some(magic_school) = &self.magic_school {
-            if MagicSchool::from_str(magic_school).is_err() {
-                return Err(SpellValidationError::InvalidMagicSchool(
-                    magic_school.to_string(),
+impl Validate<FilterValidationError> for SpellFilters {
+    fn validate(&self) -> Result<(), FilterValidationError> {
+        if let Some(duration_min_minutes) = self.duration_min_minutes {
+            if !DURATION_MIN_MINUTES.contains(&duration_min_minutes) {
+                return Err(FilterValidationError::InvalidDurationMinMinutes(
+                    duration_min_minutes,
                 ));
             }
         }
+        match &self.filter {
+            Some(filter) => filter.validate(),
+            None => Ok(()),
+        }
+    }
+}
 
-        Ok(())
+fn parse_dice(dice: &str) -> Result<Dice, SpellValidationError> {
+    let parsed =
+        Dice::from_str(dice).map_err(|_| SpellValidationError::InvalidDice(dice.to_string()))?;
+    if parsed.count > MAX_DICE || parsed.modifier > MAX_DICE {
+        return Err(SpellValidationError::TooManyDice(dice.to_string()));
+    }
+    Ok(parsed)
+}
+
+/// Parses the damage and healing dice of a spell, whichever are given.
+fn parse_effect_dice(
+    damage_dice: Option<&str>,
+    healing_dice: Option<&str>,
+) -> Result<Vec<Dice>, SpellValidationError> {
+    damage_dice
+        .into_iter()
+        .chain(healing_dice)
+        .map(parse_dice)
+        .collect()
+}
+
+/// Higher level dice have to add to at least one of the damage or healing dice.
+fn validate_higher_level_dice(
+    effect_dice: &[Dice],
+    higher_level_dice: Dice,
+) -> Result<(), SpellValidationError> {
+    if effect_dice
+        .iter()
+        .all(|effect_dice| effect_dice.sides != higher_level_dice.sides)
+    {
+        return Err(SpellValidationError::MismatchedHigherLevelDice(
+            higher_level_dice.to_string(),
+            effect_dice[0].to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_effect_metadata(
+    damage_types: &[String],
+    save_ability: Option<&str>,
+    attack_type: Option<&str>,
+    conditions: &[String],
+) -> Result<(), SpellValidationError> {
+    for damage_type in damage_types {
+        if DamageType::from_str(damage_type).is_err() {
+            return Err(SpellValidationError::InvalidDamageType(
+                damage_type.to_string(),
+            ));
+        }
+    }
+
+    if let Some(save_ability) = save_ability {
+        if Ability::from_str(save_ability).is_err() {
+            return Err(SpellValidationError::InvalidSaveAbility(
+                save_ability.to_string(),
+            ));
+        }
+    }
+
+    if let Some(attack_type) = attack_type {
+        if AttackType::from_str(attack_type).is_err() {
+            return Err(SpellValidationError::InvalidAttackType(
+                attack_type.to_string(),
+            ));
+        }
+    }
+
+    for condition in conditions {
+        if Condition::from_str(condition).is_err() {
+            return Err(SpellValidationError::InvalidCondition(
+                condition.to_string(),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_casting_time(
+    unit: &str,
+    quantity: Option<i32>,
+    reaction_trigger: Option<&str>,
+) -> Result<(), SpellValidationError> {
+    let unit = CastingTimeUnit::from_str(unit)
+        .map_err(|_| SpellValidationError::InvalidCastingTimeUnit(unit.to_string()))?;
+
+    if let Some(quantity) = quantity {
+        if quantity <= 0 {
+            return Err(SpellValidationError::InvalidCastingTimeQuantity(quantity));
+        }
+        if quantity > 1
+            && matches!(
+                unit,
+                CastingTimeUnit::Action | CastingTimeUnit::BonusAction | CastingTimeUnit::Reaction
+            )
+        {
+            return Err(SpellValidationError::CastingTimeQuantityWithoutDuration);
+        }
+    }
+
+    if reaction_trigger.is_some() && !matches!(unit, CastingTimeUnit::Reaction) {
+        return Err(SpellValidationError::ReactionTriggerWithoutReaction);
+    }
+
+    Ok(())
+}
+
+fn validate_range(
+    range_type: &str,
+    range_distance: Option<i32>,
+    range_unit: Option<&str>,
+) -> Result<(), SpellValidationError> {
+    let range_type = RangeType::from_str(range_type)
+        .map_err(|_| SpellValidationError::InvalidRangeType(range_type.to_string()))?;
+
+    if let Some(range_unit) = range_unit {
+        if DistanceUnit::from_str(range_unit).is_err() {
+            return Err(SpellValidationError::InvalidDistanceUnit(
+                range_unit.to_string(),
+            ));
+        }
+    }
+
+    if let Some(range_distance) = range_distance {
+        if !RANGE_DISTANCES.contains(&range_distance) {
+            return Err(SpellValidationError::InvalidRangeDistance(range_distance));
+        }
+    }
+
+    let has_distance = range_distance.is_some() || range_unit.is_some();
+    match range_type {
+        RangeType::Distance if range_distance.is_none() => {
+            Err(SpellValidationError::RangeDistanceWithoutDistanceRange)
+        }
+        RangeType::Distance => Ok(()),
+        _ if has_distance => Err(SpellValidationError::RangeDistanceWithoutDistanceRange),
+        _ => Ok(()),
+    }
+}
+
+fn validate_area(
+    area_shape: Option<&str>,
+    area_size: Option<i32>,
+) -> Result<(), SpellValidationError> {
+    if let Some(area_shape) = area_shape {
+        if AreaShape::from_str(area_shape).is_err() {
+            return Err(SpellValidationError::InvalidAreaShape(
+                area_shape.to_string(),
+            ));
+        }
+    }
+
+    if let Some(area_size) = area_size {
+        if area_size <= 0 {
+            return Err(SpellValidationError::InvalidAreaSize(area_size));
+        }
+    }
+
+    if area_shape.is_some() != area_size.is_some() {
+        return Err(SpellValidationError::IncompleteArea);
+    }
+
+    Ok(())
+}
+
+fn is_timed(unit: &DurationUnit) -> bool {
+    matches!(
+        unit,
+        DurationUnit::Round | DurationUnit::Minute | DurationUnit::Hour | DurationUnit::Day
+    )
+}
+
+fn validate_duration(
+    unit: &str,
+    quantity: Option<i32>,
+    up_to: bool,
+    concentration: bool,
+) -> Result<(), SpellValidationError> {
+    let unit = DurationUnit::from_str(unit)
+        .map_err(|_| SpellValidationError::InvalidDurationUnit(unit.to_string()))?;
+
+    if let Some(quantity) = quantity {
+        if !DURATION_QUANTITIES.contains(&quantity) {
+            return Err(SpellValidationError::InvalidDurationQuantity(quantity));
+        }
+    }
+
+    if is_timed(&unit) {
+        if quantity.is_none() {
+            return Err(SpellValidationError::DurationQuantityWithoutTimedDuration);
+        }
+    } else {
+        if quantity.is_some() {
+            return Err(SpellValidationError::DurationQuantityWithoutTimedDuration);
+        }
+        if up_to {
+            return Err(SpellValidationError::UpToWithoutTimedDuration);
+        }
+    }
+
+    if concentration && unit == DurationUnit::Instantaneous {
+        return Err(SpellValidationError::ConcentrationWithInstantaneousDuration);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn filters(filters: serde_json::Value) -> SpellFilters {
+        serde_json::from_value(filters).unwrap()
+    }
+
+    #[test]
+    fn accepts_durations_up_to_the_longest() {
+        for minutes in [0, 60, 14_400_000] {
+            assert!(filters(json!({ "duration_min_minutes": minutes }))
+                .validate()
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn rejects_durations_beyond_the_longest() {
+        for minutes in [-1, 14_400_001, 40_000_000, i32::MAX] {
+            assert!(matches!(
+                filters(json!({ "duration_min_minutes": minutes })).validate(),
+                Err(FilterValidationError::InvalidDurationMinMinutes(invalid)) if invalid == minutes
+            ));
+        }
     }
 }